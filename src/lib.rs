@@ -1,54 +1,200 @@
+use std::fmt::{self, Display, Formatter};
+
 pub fn uniform_width_ascii_lines<'a>(
     input: impl Iterator<Item = &'a str> + Clone + 'a,
 ) -> impl Iterator<Item = &'a str> + Clone + 'a {
+    try_uniform_width_ascii_lines(input).map(|line| line.unwrap_or_else(|e| panic!("{e}")))
+}
+
+/// Like [`uniform_width_ascii_lines`], but yields a [`GridShapeError`] for each offending line
+/// instead of panicking.
+pub fn try_uniform_width_ascii_lines<'a>(
+    input: impl Iterator<Item = &'a str> + Clone + 'a,
+) -> impl Iterator<Item = Result<&'a str, GridShapeError>> + Clone + 'a {
     let mut first_line_width = None;
-    input
-        .inspect(move |line| match (first_line_width, line.len()) {
+    input.enumerate().map(move |(line_idx, line)| {
+        let line_num = line_idx + 1;
+        if let Some(byte_offset) = line.bytes().position(|b| !b.is_ascii()) {
+            return Err(GridShapeError {
+                line_num,
+                kind: GridShapeErrorKind::NonAscii { byte_offset },
+            });
+        }
+        match (first_line_width, line.len()) {
             (None, len) => first_line_width = Some(len),
             (Some(expected), actual) => {
-                assert_eq!(expected, actual, "line does not match first line's length")
+                if expected != actual {
+                    return Err(GridShapeError {
+                        line_num,
+                        kind: GridShapeErrorKind::WidthMismatch { expected, actual },
+                    });
+                }
             }
-        })
-        .inspect(|line| assert!(line.is_ascii()))
+        }
+        Ok(line)
+    })
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct GridShapeError {
+    /// 1-based.
+    pub line_num: usize,
+    pub kind: GridShapeErrorKind,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum GridShapeErrorKind {
+    WidthMismatch { expected: usize, actual: usize },
+    NonAscii { byte_offset: usize },
+}
+
+impl Display for GridShapeError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let Self { line_num, kind } = self;
+        match kind {
+            GridShapeErrorKind::WidthMismatch { expected, actual } => write!(
+                f,
+                "line {line_num} has width {actual}, but the first line's width is {expected}"
+            ),
+            GridShapeErrorKind::NonAscii { byte_offset } => write!(
+                f,
+                "line {line_num} has a non-ASCII character at byte offset {byte_offset}"
+            ),
+        }
+    }
 }
 
+impl std::error::Error for GridShapeError {}
+
 pub mod space {
-    use std::ops::{Neg, Sub};
+    use std::{
+        fmt::{self, Debug, Formatter},
+        hash::{Hash, Hasher},
+        marker::PhantomData,
+        ops::{Neg, Sub},
+    };
 
-    #[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
-    pub struct Coord {
+    /// Marker types for [`Coord`]'s axis parameter, so that, e.g., a row can't be used where a
+    /// column is expected.
+    pub mod axis {
+        #[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+        pub enum Any {}
+
+        #[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+        pub enum Row {}
+
+        #[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+        pub enum Col {}
+    }
+
+    pub type RowCoord = Coord<axis::Row>;
+    pub type ColCoord = Coord<axis::Col>;
+
+    #[derive(Clone, Copy, Eq, Hash, Ord, PartialEq, PartialOrd)]
+    pub struct Coord<A = axis::Any> {
         inner: usize,
+        axis: PhantomData<A>,
+    }
+
+    impl<A> Debug for Coord<A> {
+        fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+            let Self { inner, axis: _ } = self;
+            f.debug_struct("Coord").field("inner", inner).finish()
+        }
     }
 
-    impl Coord {
+    impl<A> Coord<A> {
         pub fn new(inner: usize) -> Self {
-            Self { inner }
+            Self {
+                inner,
+                axis: PhantomData,
+            }
         }
 
         pub fn into_inner(self) -> usize {
-            let Self { inner } = self;
+            let Self { inner, axis: _ } = self;
             inner
         }
 
+        /// Reinterprets this coordinate as belonging to axis `B`.
+        pub fn into_axis<B>(self) -> Coord<B> {
+            Coord::new(self.into_inner())
+        }
+
+        /// Returns `None` if the result would be negative.
+        ///
+        /// # Panics
+        ///
+        /// If the result would overflow `usize`.
         #[track_caller]
         pub fn rel_offset(self, offset: RelativeOffset) -> Option<Self> {
-            let Self { inner } = self;
+            let inner = self.inner;
+            let result = self.checked_rel_offset(offset);
+            if result.is_none() && offset.sign == PosNeg::Positive {
+                panic!("applying {offset:?} to coordinate {inner} overflowed");
+            }
+            result
+        }
+
+        /// Like [`Self::rel_offset`], but also returns `None` unless the result is below `bound`.
+        #[track_caller]
+        fn rel_offset_below(self, offset: RelativeOffset, bound: Self) -> Option<Self> {
+            self.rel_offset(offset).filter(|o| o.inner < bound.inner)
+        }
+
+        /// Like [`Self::rel_offset`], but returns `None` on overflow instead of panicking.
+        pub fn checked_rel_offset(self, offset: RelativeOffset) -> Option<Self> {
+            let Self { inner, axis } = self;
             let RelativeOffset { value, sign } = offset;
             let Offset { inner: value } = value;
             (match sign {
-                PosNeg::Positive => Some(inner.checked_add(value).unwrap()),
+                PosNeg::Positive => inner.checked_add(value),
                 PosNeg::Negative => inner.checked_sub(value),
             })
-            .map(|inner| Self { inner })
+            .map(|inner| Self { inner, axis })
+        }
+
+        /// Applies `offset` modulo `modulus`, treating the axis as if it wraps around. `offset`
+        /// may be arbitrarily large.
+        #[track_caller]
+        pub fn wrapping_rel_offset(self, offset: RelativeOffset, modulus: Self) -> Self {
+            let Self { inner, axis } = self;
+            let Self {
+                inner: modulus,
+                axis: _,
+            } = modulus;
+            assert!(
+                inner < modulus,
+                "coordinate {inner} is out of bounds of modulus {modulus}"
+            );
+            let RelativeOffset { value, sign } = offset;
+            let Offset { inner: value } = value;
+            let value = value % modulus;
+            let inner = match sign {
+                PosNeg::Positive => {
+                    if value >= modulus - inner {
+                        inner - (modulus - value)
+                    } else {
+                        inner + value
+                    }
+                }
+                PosNeg::Negative => {
+                    if value <= inner {
+                        inner - value
+                    } else {
+                        inner + (modulus - value)
+                    }
+                }
+            };
+            Self { inner, axis }
         }
     }
 
-    impl Sub for Coord {
+    impl<A> Sub for Coord<A> {
         type Output = RelativeOffset;
 
         fn sub(self, rhs: Self) -> Self::Output {
-            let Self { inner: lhs } = self;
-            let Self { inner: rhs } = rhs;
+            let [lhs, rhs] = [self, rhs].map(Coord::into_inner);
             RelativeOffset {
                 value: Offset::new(lhs.abs_diff(rhs)),
                 sign: lhs
@@ -65,15 +211,30 @@ pub mod space {
     }
 
     impl Offset {
-        pub fn new(inner: usize) -> Self {
+        pub const fn new(inner: usize) -> Self {
             Self { inner }
         }
 
+        pub fn into_inner(self) -> usize {
+            let Self { inner } = self;
+            inner
+        }
+
         pub fn checked_mul(self, rhs: usize) -> Option<Self> {
             let Self { inner } = self;
             let inner = inner.checked_mul(rhs)?;
             Some(Self { inner })
         }
+
+        /// Computes `(self * rhs) % modulus` without overflowing.
+        #[track_caller]
+        pub fn mul_rem(self, rhs: usize, modulus: usize) -> Self {
+            assert_ne!(modulus, 0, "can't reduce modulo 0");
+            let Self { inner } = self;
+            let [inner, rhs, modulus] = [inner, rhs, modulus].map(|x| u128::try_from(x).unwrap());
+            let inner = usize::try_from((inner % modulus) * (rhs % modulus) % modulus).unwrap();
+            Self { inner }
+        }
     }
 
     #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
@@ -91,12 +252,34 @@ pub mod space {
         }
     }
 
-    #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+    /// Note that a zero `value` compares equal regardless of its `sign`.
+    #[derive(Clone, Copy, Debug, Eq)]
     pub struct RelativeOffset {
         pub value: Offset,
         pub sign: PosNeg,
     }
 
+    impl PartialEq for RelativeOffset {
+        fn eq(&self, other: &Self) -> bool {
+            let &Self { value, sign } = self;
+            let &Self {
+                value: other_value,
+                sign: other_sign,
+            } = other;
+            value == other_value && (sign == other_sign || value.into_inner() == 0)
+        }
+    }
+
+    impl Hash for RelativeOffset {
+        fn hash<H: Hasher>(&self, state: &mut H) {
+            let Self { value, sign } = self;
+            value.hash(state);
+            if value.into_inner() != 0 {
+                sign.hash(state);
+            }
+        }
+    }
+
     impl Neg for RelativeOffset {
         type Output = Self;
 
@@ -116,24 +299,159 @@ pub mod space {
             let value = value.checked_mul(rhs)?;
             Some(Self { value, sign })
         }
+
+        /// Computes `self * rhs` with its magnitude reduced modulo `modulus`, which is all that
+        /// matters to [`Coord::wrapping_rel_offset`] for an axis of that length.
+        #[track_caller]
+        pub fn mul_rem(self, rhs: usize, modulus: usize) -> Self {
+            let Self { value, sign } = self;
+            let value = value.mul_rem(rhs, modulus);
+            Self { value, sign }
+        }
+
+        pub fn checked_add(self, rhs: Self) -> Option<Self> {
+            let Self {
+                value: Offset { inner: lhs },
+                sign: lhs_sign,
+            } = self;
+            let Self {
+                value: Offset { inner: rhs },
+                sign: rhs_sign,
+            } = rhs;
+            let (value, sign) = if lhs_sign == rhs_sign {
+                (lhs.checked_add(rhs)?, lhs_sign)
+            } else if lhs >= rhs {
+                (lhs - rhs, lhs_sign)
+            } else {
+                (rhs - lhs, rhs_sign)
+            };
+            let value = Offset::new(value);
+            Some(Self { value, sign })
+        }
+
+        pub fn checked_sub(self, rhs: Self) -> Option<Self> {
+            self.checked_add(-rhs)
+        }
+
+        /// Divides by `rhs`, returning `None` unless `rhs` divides the magnitude exactly.
+        pub fn checked_div_exact(self, rhs: usize) -> Option<Self> {
+            let Self { value, sign } = self;
+            let Offset { inner } = value;
+            if rhs == 0 || inner % rhs != 0 {
+                return None;
+            }
+            let value = Offset::new(inner / rhs);
+            Some(Self { value, sign })
+        }
+    }
+
+    impl TryFrom<RelativeOffset> for i64 {
+        type Error = std::num::TryFromIntError;
+
+        fn try_from(value: RelativeOffset) -> Result<Self, Self::Error> {
+            let RelativeOffset { value, sign } = value;
+            // NOTE: Going through `i128` lets `i64::MIN`, whose magnitude doesn't fit in an
+            // `i64`, convert successfully.
+            let magnitude = i128::try_from(value.into_inner())?;
+            i64::try_from(match sign {
+                PosNeg::Positive => magnitude,
+                PosNeg::Negative => -magnitude,
+            })
+        }
+    }
+
+    impl From<i64> for RelativeOffset {
+        fn from(value: i64) -> Self {
+            Self {
+                value: Offset::new(usize::try_from(value.unsigned_abs()).unwrap()),
+                sign: if value < 0 {
+                    PosNeg::Negative
+                } else {
+                    PosNeg::Positive
+                },
+            }
+        }
+    }
+
+    fn gcd(mut a: usize, mut b: usize) -> usize {
+        while b != 0 {
+            (a, b) = (b, a % b);
+        }
+        a
     }
 
     pub mod d2 {
         use std::ops::{Neg, Sub};
 
-        use super::Coord;
+        use super::{ColCoord, Coord, RowCoord};
 
         #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
         pub struct Size {
-            pub row: Coord,
-            pub col: Coord,
+            pub row: RowCoord,
+            pub col: ColCoord,
         }
 
         impl Size {
+            /// `coords` is `(num_rows, num_cols)`.
             pub fn from_row_major(coords: (usize, usize)) -> Self {
                 let (row, col) = coords;
-                let [row, col] = [row, col].map(Coord::new);
-                Self { row, col }
+                Self {
+                    row: Coord::new(row),
+                    col: Coord::new(col),
+                }
+            }
+
+            /// `(num_rows, num_cols)`
+            pub fn into_row_major(self) -> (usize, usize) {
+                let Self { row, col } = self;
+                (row.into_inner(), col.into_inner())
+            }
+
+            /// `coords` is `(width, height)`.
+            pub fn from_xy(coords: (usize, usize)) -> Self {
+                let (x, y) = coords;
+                Self::from_row_major((y, x))
+            }
+
+            /// `(width, height)`
+            pub fn into_xy(self) -> (usize, usize) {
+                let (row, col) = self.into_row_major();
+                (col, row)
+            }
+
+            pub fn contains(self, coords: Coords) -> bool {
+                let Self { row, col } = self;
+                coords.row < row && coords.col < col
+            }
+
+            pub fn area(self) -> usize {
+                let (num_rows, num_cols) = self.into_row_major();
+                num_rows.checked_mul(num_cols).unwrap()
+            }
+
+            /// The index of `coords` in a flat, row-major buffer of this size.
+            pub fn row_major_idx(self, coords: Coords) -> Option<usize> {
+                self.contains(coords).then(|| {
+                    let (row_idx, col_idx) = coords.into_row_major();
+                    let (_num_rows, num_cols) = self.into_row_major();
+                    row_idx * num_cols + col_idx
+                })
+            }
+
+            /// The inverse of [`Self::row_major_idx`].
+            pub fn coords_at_row_major_idx(self, idx: usize) -> Option<Coords> {
+                (idx < self.area()).then(|| {
+                    let (_num_rows, num_cols) = self.into_row_major();
+                    Coords::from_row_major((idx / num_cols, idx % num_cols))
+                })
+            }
+
+            /// Iterates over all in-bounds [`Coords`] in row-major order.
+            pub fn coords(self) -> impl Iterator<Item = Coords> + Clone {
+                let (num_rows, num_cols) = self.into_row_major();
+                (0..num_rows).flat_map(move |row_idx| {
+                    (0..num_cols).map(move |col_idx| Coords::from_row_major((row_idx, col_idx)))
+                })
             }
         }
 
@@ -150,6 +468,57 @@ pub mod space {
                 let col = col.checked_mul(rhs)?;
                 Some(Self { row, col })
             }
+
+            pub fn checked_add(self, rhs: Self) -> Option<Self> {
+                let Self { row, col } = self;
+                let row = row.checked_add(rhs.row)?;
+                let col = col.checked_add(rhs.col)?;
+                Some(Self { row, col })
+            }
+
+            pub fn checked_sub(self, rhs: Self) -> Option<Self> {
+                self.checked_add(-rhs)
+            }
+
+            /// Divides both axes by `rhs`, returning `None` unless `rhs` divides both exactly.
+            pub fn checked_div_exact(self, rhs: usize) -> Option<Self> {
+                let Self { row, col } = self;
+                let row = row.checked_div_exact(rhs)?;
+                let col = col.checked_div_exact(rhs)?;
+                Some(Self { row, col })
+            }
+
+            pub fn is_zero(self) -> bool {
+                let Self { row, col } = self;
+                [row, col].iter().all(|axis| axis.value.into_inner() == 0)
+            }
+
+            /// Divides out the GCD of both axes, yielding the smallest step in the same direction
+            /// that still lands on integer coordinates. A zero offset is returned as-is.
+            pub fn reduced(self) -> Self {
+                let Self { row, col } = self;
+                match super::gcd(row.value.into_inner(), col.value.into_inner()) {
+                    0 => self,
+                    divisor => self.checked_div_exact(divisor).unwrap(),
+                }
+            }
+        }
+
+        impl TryFrom<RelativeOffset> for (i64, i64) {
+            type Error = std::num::TryFromIntError;
+
+            fn try_from(value: RelativeOffset) -> Result<Self, Self::Error> {
+                let RelativeOffset { row, col } = value;
+                Ok((row.try_into()?, col.try_into()?))
+            }
+        }
+
+        impl From<(i64, i64)> for RelativeOffset {
+            fn from(value: (i64, i64)) -> Self {
+                let (row, col) = value;
+                let [row, col] = [row, col].map(super::RelativeOffset::from);
+                Self { row, col }
+            }
         }
 
         impl Neg for RelativeOffset {
@@ -167,15 +536,37 @@ pub mod space {
 
         #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
         pub struct Coords {
-            pub row: Coord,
-            pub col: Coord,
+            pub row: RowCoord,
+            pub col: ColCoord,
         }
 
         impl Coords {
+            /// `coords` is `(row, col)`.
             pub fn from_row_major(coords: (usize, usize)) -> Self {
                 let (row, col) = coords;
-                let [row, col] = [row, col].map(Coord::new);
-                Self { row, col }
+                Self {
+                    row: Coord::new(row),
+                    col: Coord::new(col),
+                }
+            }
+
+            /// `(row, col)`
+            pub fn into_row_major(self) -> (usize, usize) {
+                let Self { row, col } = self;
+                (row.into_inner(), col.into_inner())
+            }
+
+            /// `coords` is `(x, y)`, where `x` is the column and `y` is the row (i.e., `y` grows
+            /// downwards).
+            pub fn from_xy(coords: (usize, usize)) -> Self {
+                let (x, y) = coords;
+                Self::from_row_major((y, x))
+            }
+
+            /// `(x, y)`; see [`Self::from_xy`].
+            pub fn into_xy(self) -> (usize, usize) {
+                let (row, col) = self.into_row_major();
+                (col, row)
             }
         }
 
@@ -192,13 +583,19 @@ pub mod space {
                     col: rhs_col,
                 } = rhs;
 
-                let [row, col] =
-                    [(lhs_row, rhs_row), (lhs_col, rhs_col)].map(|(lhs, rhs)| lhs - rhs);
-
-                RelativeOffset { row, col }
+                RelativeOffset {
+                    row: lhs_row - rhs_row,
+                    col: lhs_col - rhs_col,
+                }
             }
         }
 
+        /// Returns `None` if the result is out of `bounds`.
+        ///
+        /// # Panics
+        ///
+        /// If the result would overflow `usize`.
+        #[track_caller]
         pub fn apply_rel_offset(
             bounds: Size,
             origin: Coords,
@@ -214,60 +611,3725 @@ pub mod space {
                 col: bounds_col,
             } = bounds;
 
-            let row = origin_row.rel_offset(row).filter(|o| *o < bounds_row)?;
-            let col = origin_col.rel_offset(col).filter(|o| *o < bounds_col)?;
+            let row = origin_row.rel_offset_below(row, bounds_row)?;
+            let col = origin_col.rel_offset_below(col, bounds_col)?;
             Some(Coords { row, col })
         }
-    }
-}
 
-pub mod search_direction {
-    #[derive(Clone, Copy, Debug, Eq, PartialEq, strum::EnumIter)]
-    pub enum Sign {
-        Neutral,
-        Negative,
-        Positive,
-    }
+        /// Like [`apply_rel_offset`], but returns `None` on overflow instead of panicking.
+        pub fn checked_apply_rel_offset(
+            bounds: Size,
+            origin: Coords,
+            offset: RelativeOffset,
+        ) -> Option<Coords> {
+            let RelativeOffset { row, col } = offset;
+            let Coords {
+                row: origin_row,
+                col: origin_col,
+            } = origin;
+            let Size {
+                row: bounds_row,
+                col: bounds_col,
+            } = bounds;
 
-    #[derive(Clone)]
-    pub struct SearchDirection {
-        pub horizontal: Sign,
-        pub vertical: Sign,
-    }
+            let row = origin_row
+                .checked_rel_offset(row)
+                .filter(|o| *o < bounds_row)?;
+            let col = origin_col
+                .checked_rel_offset(col)
+                .filter(|o| *o < bounds_col)?;
+            Some(Coords { row, col })
+        }
 
-    impl SearchDirection {
-        pub fn to_2d_offsets(
-            &self,
-            origin: (usize, usize),
-            bounds: (usize, usize),
-            offset: usize,
-        ) -> Option<(usize, usize)> {
-            let &Self {
-                horizontal,
-                vertical,
-            } = self;
+        /// Iterates over `origin`, `origin + step`, `origin + 2 * step`, and so on, stopping at
+        /// the first point outside of `bounds`. A zero `step` yields `origin` only once.
+        pub fn ray(bounds: Size, origin: Coords, step: RelativeOffset) -> Ray {
+            Ray {
+                bounds,
+                next: bounds.contains(origin).then_some(origin),
+                step,
+            }
+        }
 
-            let convert = |value, sign| {
-                let (value, sign) = match sign {
-                    Sign::Positive => (value, crate::space::PosNeg::Positive),
-                    Sign::Neutral => (0, crate::space::PosNeg::Positive),
-                    Sign::Negative => (value, crate::space::PosNeg::Negative),
-                };
-                let value = crate::space::Offset::new(value);
-                crate::space::RelativeOffset { value, sign }
-            };
+        /// Like [`ray`], but continues with the points behind `origin` (i.e., along `-step`)
+        /// once the points ahead of it have been exhausted.
+        pub fn bidirectional_ray(
+            bounds: Size,
+            origin: Coords,
+            step: RelativeOffset,
+        ) -> impl Iterator<Item = Coords> + Clone {
+            ray(bounds, origin, step).chain(ray(bounds, origin, -step).skip(1))
+        }
 
-            let bounds = crate::space::d2::Size::from_row_major(bounds);
-            let origin = crate::space::d2::Coords::from_row_major(origin);
-            let offset = crate::space::d2::RelativeOffset {
-                row: convert(offset, vertical),
-                col: convert(offset, horizontal),
-            };
+        #[derive(Clone, Debug)]
+        pub struct Ray {
+            bounds: Size,
+            next: Option<Coords>,
+            step: RelativeOffset,
+        }
+
+        impl Iterator for Ray {
+            type Item = Coords;
+
+            fn next(&mut self) -> Option<Self::Item> {
+                let Self { bounds, next, step } = self;
+                let current = next.take()?;
+                if !step.is_zero() {
+                    *next = checked_apply_rel_offset(*bounds, current, *step);
+                }
+                Some(current)
+            }
+        }
+
+        impl std::iter::FusedIterator for Ray {}
+
+        const fn unit_step(row: super::PosNeg, col: super::PosNeg) -> RelativeOffset {
+            RelativeOffset {
+                row: super::RelativeOffset {
+                    value: super::Offset::new(1),
+                    sign: row,
+                },
+                col: super::RelativeOffset {
+                    value: super::Offset::new(1),
+                    sign: col,
+                },
+            }
+        }
+
+        const fn axis_step(sign: super::PosNeg) -> super::RelativeOffset {
+            super::RelativeOffset {
+                value: super::Offset::new(1),
+                sign,
+            }
+        }
+
+        const NO_STEP: super::RelativeOffset = super::RelativeOffset {
+            value: super::Offset::new(0),
+            sign: super::PosNeg::Positive,
+        };
+
+        /// Up, right, down and left, in that (clockwise) order.
+        pub const VON_NEUMANN_STENCIL: [RelativeOffset; 4] = {
+            use super::PosNeg::{Negative, Positive};
+            [
+                RelativeOffset {
+                    row: axis_step(Negative),
+                    col: NO_STEP,
+                },
+                RelativeOffset {
+                    row: NO_STEP,
+                    col: axis_step(Positive),
+                },
+                RelativeOffset {
+                    row: axis_step(Positive),
+                    col: NO_STEP,
+                },
+                RelativeOffset {
+                    row: NO_STEP,
+                    col: axis_step(Negative),
+                },
+            ]
+        };
+
+        /// All eight adjacent directions, clockwise from up.
+        pub const MOORE_STENCIL: [RelativeOffset; 8] = {
+            use super::PosNeg::{Negative, Positive};
+            let [up, right, down, left] = VON_NEUMANN_STENCIL;
+            [
+                up,
+                unit_step(Negative, Positive),
+                right,
+                unit_step(Positive, Positive),
+                down,
+                unit_step(Positive, Negative),
+                left,
+                unit_step(Negative, Negative),
+            ]
+        };
+
+        /// Yields each offset in `stencil` paired with its result when applied to `origin`,
+        /// skipping those that land outside of `bounds`.
+        pub fn neighbors_with(
+            bounds: Size,
+            origin: Coords,
+            stencil: &[RelativeOffset],
+        ) -> impl Iterator<Item = (RelativeOffset, Coords)> + Clone + '_ {
+            stencil.iter().copied().filter_map(move |offset| {
+                let coords = checked_apply_rel_offset(bounds, origin, offset)?;
+                Some((offset, coords))
+            })
+        }
+
+        /// [`neighbors_with`] the [`VON_NEUMANN_STENCIL`].
+        pub fn neighbors4(
+            bounds: Size,
+            origin: Coords,
+        ) -> impl Iterator<Item = (RelativeOffset, Coords)> + Clone {
+            neighbors_with(bounds, origin, &VON_NEUMANN_STENCIL)
+        }
+
+        /// [`neighbors_with`] the [`MOORE_STENCIL`].
+        pub fn neighbors8(
+            bounds: Size,
+            origin: Coords,
+        ) -> impl Iterator<Item = (RelativeOffset, Coords)> + Clone {
+            neighbors_with(bounds, origin, &MOORE_STENCIL)
+        }
+
+        /// Like [`apply_rel_offset`], but wraps around each axis of `bounds` (i.e., as on a
+        /// torus) instead of rejecting results outside of it.
+        #[track_caller]
+        pub fn apply_rel_offset_wrapping(
+            bounds: Size,
+            origin: Coords,
+            offset: RelativeOffset,
+        ) -> Coords {
+            let RelativeOffset { row, col } = offset;
+            let Coords {
+                row: origin_row,
+                col: origin_col,
+            } = origin;
+            let Size {
+                row: bounds_row,
+                col: bounds_col,
+            } = bounds;
+
+            let row = origin_row.wrapping_rel_offset(row, bounds_row);
+            let col = origin_col.wrapping_rel_offset(col, bounds_col);
+            Coords { row, col }
+        }
+
+        /// Applies `step` `times` times with [`apply_rel_offset_wrapping`], in constant time and
+        /// without overflowing, even when `step.checked_mul(times)` would.
+        #[track_caller]
+        pub fn apply_rel_offset_wrapping_n(
+            bounds: Size,
+            origin: Coords,
+            step: RelativeOffset,
+            times: usize,
+        ) -> Coords {
+            assert!(
+                bounds.contains(origin),
+                "{origin:?} is out of bounds of {bounds:?}"
+            );
+            let RelativeOffset { row, col } = step;
+            let Size {
+                row: bounds_row,
+                col: bounds_col,
+            } = bounds;
+
+            let offset = RelativeOffset {
+                row: row.mul_rem(times, bounds_row.into_inner()),
+                col: col.mul_rem(times, bounds_col.into_inner()),
+            };
+            apply_rel_offset_wrapping(bounds, origin, offset)
+        }
+    }
+
+    pub mod d3 {
+        use std::ops::{Neg, Sub};
+
+        use super::Coord;
+
+        #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+        pub struct Size {
+            pub x: Coord,
+            pub y: Coord,
+            pub z: Coord,
+        }
+
+        impl Size {
+            pub fn from_xyz(coords: (usize, usize, usize)) -> Self {
+                let (x, y, z) = coords;
+                let [x, y, z] = [x, y, z].map(Coord::new);
+                Self { x, y, z }
+            }
+
+            pub fn into_xyz(self) -> (usize, usize, usize) {
+                let Self { x, y, z } = self;
+                (x.into_inner(), y.into_inner(), z.into_inner())
+            }
+
+            pub fn contains(self, coords: Coords) -> bool {
+                let Self { x, y, z } = self;
+                coords.x < x && coords.y < y && coords.z < z
+            }
+        }
+
+        #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+        pub struct RelativeOffset {
+            pub x: super::RelativeOffset,
+            pub y: super::RelativeOffset,
+            pub z: super::RelativeOffset,
+        }
+
+        impl RelativeOffset {
+            pub fn checked_mul(self, rhs: usize) -> Option<Self> {
+                let Self { x, y, z } = self;
+                let x = x.checked_mul(rhs)?;
+                let y = y.checked_mul(rhs)?;
+                let z = z.checked_mul(rhs)?;
+                Some(Self { x, y, z })
+            }
+        }
+
+        impl Neg for RelativeOffset {
+            type Output = Self;
+
+            fn neg(self) -> Self::Output {
+                let Self { x, y, z } = self;
+
+                Self {
+                    x: -x,
+                    y: -y,
+                    z: -z,
+                }
+            }
+        }
+
+        #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+        pub struct Coords {
+            pub x: Coord,
+            pub y: Coord,
+            pub z: Coord,
+        }
+
+        impl Coords {
+            pub fn from_xyz(coords: (usize, usize, usize)) -> Self {
+                let (x, y, z) = coords;
+                let [x, y, z] = [x, y, z].map(Coord::new);
+                Self { x, y, z }
+            }
+
+            pub fn into_xyz(self) -> (usize, usize, usize) {
+                let Self { x, y, z } = self;
+                (x.into_inner(), y.into_inner(), z.into_inner())
+            }
+        }
+
+        impl Sub for Coords {
+            type Output = RelativeOffset;
+
+            fn sub(self, rhs: Self) -> Self::Output {
+                let Self {
+                    x: lhs_x,
+                    y: lhs_y,
+                    z: lhs_z,
+                } = self;
+                let Self {
+                    x: rhs_x,
+                    y: rhs_y,
+                    z: rhs_z,
+                } = rhs;
+
+                let [x, y, z] =
+                    [(lhs_x, rhs_x), (lhs_y, rhs_y), (lhs_z, rhs_z)].map(|(lhs, rhs)| lhs - rhs);
+
+                RelativeOffset { x, y, z }
+            }
+        }
+
+        /// Returns `None` if the result is out of `bounds`.
+        ///
+        /// # Panics
+        ///
+        /// If the result would overflow `usize`.
+        #[track_caller]
+        pub fn apply_rel_offset(
+            bounds: Size,
+            origin: Coords,
+            offset: RelativeOffset,
+        ) -> Option<Coords> {
+            let RelativeOffset { x, y, z } = offset;
+            let Coords {
+                x: origin_x,
+                y: origin_y,
+                z: origin_z,
+            } = origin;
+            let Size {
+                x: bounds_x,
+                y: bounds_y,
+                z: bounds_z,
+            } = bounds;
+
+            let x = origin_x.rel_offset_below(x, bounds_x)?;
+            let y = origin_y.rel_offset_below(y, bounds_y)?;
+            let z = origin_z.rel_offset_below(z, bounds_z)?;
+            Some(Coords { x, y, z })
+        }
+    }
+
+    /// N-dimensional counterparts of the types in [`d2`] and [`d3`], with axes stored in arrays.
+    pub mod nd {
+        use std::ops::{Neg, Sub};
+
+        use super::{Coord, Offset, PosNeg};
+
+        #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+        pub struct Size<const N: usize> {
+            pub axes: [Coord; N],
+        }
+
+        impl<const N: usize> Size<N> {
+            pub fn from_array(axes: [usize; N]) -> Self {
+                let axes = axes.map(Coord::new);
+                Self { axes }
+            }
+
+            pub fn into_array(self) -> [usize; N] {
+                let Self { axes } = self;
+                axes.map(Coord::into_inner)
+            }
+
+            pub fn contains(self, coords: Coords<N>) -> bool {
+                let Self { axes } = self;
+                axes.iter()
+                    .zip(coords.axes)
+                    .all(|(bound, coord)| coord < *bound)
+            }
+        }
+
+        #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+        pub struct RelativeOffset<const N: usize> {
+            pub axes: [super::RelativeOffset; N],
+        }
+
+        impl<const N: usize> RelativeOffset<N> {
+            pub fn checked_mul(self, rhs: usize) -> Option<Self> {
+                let Self { axes } = self;
+                let mut multiplied = axes;
+                for axis in &mut multiplied {
+                    *axis = axis.checked_mul(rhs)?;
+                }
+                Some(Self { axes: multiplied })
+            }
+        }
+
+        impl<const N: usize> Neg for RelativeOffset<N> {
+            type Output = Self;
+
+            fn neg(self) -> Self::Output {
+                let Self { axes } = self;
+
+                Self {
+                    axes: axes.map(|axis| -axis),
+                }
+            }
+        }
+
+        #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+        pub struct Coords<const N: usize> {
+            pub axes: [Coord; N],
+        }
+
+        impl<const N: usize> Coords<N> {
+            pub fn from_array(axes: [usize; N]) -> Self {
+                let axes = axes.map(Coord::new);
+                Self { axes }
+            }
+
+            pub fn into_array(self) -> [usize; N] {
+                let Self { axes } = self;
+                axes.map(Coord::into_inner)
+            }
+        }
+
+        impl<const N: usize> Sub for Coords<N> {
+            type Output = RelativeOffset<N>;
+
+            fn sub(self, rhs: Self) -> Self::Output {
+                let Self { axes: lhs } = self;
+                let Self { axes: rhs } = rhs;
+
+                RelativeOffset {
+                    axes: std::array::from_fn(|idx| lhs[idx] - rhs[idx]),
+                }
+            }
+        }
+
+        /// Returns `None` if the result is out of `bounds`.
+        ///
+        /// # Panics
+        ///
+        /// If the result would overflow `usize`.
+        #[track_caller]
+        pub fn apply_rel_offset<const N: usize>(
+            bounds: Size<N>,
+            origin: Coords<N>,
+            offset: RelativeOffset<N>,
+        ) -> Option<Coords<N>> {
+            let RelativeOffset { axes: offset } = offset;
+            let Coords { axes: origin } = origin;
+            let Size { axes: bounds } = bounds;
+
+            let mut axes = origin;
+            for ((axis, offset), bound) in axes.iter_mut().zip(offset).zip(bounds) {
+                *axis = axis.rel_offset_below(offset, bound)?;
+            }
+            Some(Coords { axes })
+        }
+
+        /// All offsets with each axis in `-1..=1`, excluding the all-zero offset; i.e., the
+        /// `3^N - 1` directions of a Moore neighbourhood.
+        pub fn moore_offsets<const N: usize>() -> impl Iterator<Item = RelativeOffset<N>> + Clone {
+            let num_offsets = 3usize.pow(u32::try_from(N).unwrap());
+            (0..num_offsets).filter_map(|mut idx| {
+                let mut is_zero = true;
+                let axes = std::array::from_fn(|_axis_idx| {
+                    let digit = idx % 3;
+                    idx /= 3;
+                    let (value, sign) = match digit {
+                        0 => (0, PosNeg::Positive),
+                        1 => (1, PosNeg::Positive),
+                        2 => (1, PosNeg::Negative),
+                        _ => unreachable!(),
+                    };
+                    is_zero &= value == 0;
+                    super::RelativeOffset {
+                        value: Offset::new(value),
+                        sign,
+                    }
+                });
+                (!is_zero).then_some(RelativeOffset { axes })
+            })
+        }
+
+        /// The `2 * N` unit offsets along a single axis; i.e., the directions of a von Neumann
+        /// neighbourhood.
+        pub fn von_neumann_offsets<const N: usize>(
+        ) -> impl Iterator<Item = RelativeOffset<N>> + Clone {
+            (0..N).flat_map(|axis_idx| {
+                [PosNeg::Positive, PosNeg::Negative].map(move |sign| {
+                    let axes = std::array::from_fn(|idx| super::RelativeOffset {
+                        value: Offset::new(usize::from(idx == axis_idx)),
+                        sign: if idx == axis_idx {
+                            sign
+                        } else {
+                            PosNeg::Positive
+                        },
+                    });
+                    RelativeOffset { axes }
+                })
+            })
+        }
+
+        pub fn moore_neighbors<const N: usize>(
+            bounds: Size<N>,
+            origin: Coords<N>,
+        ) -> impl Iterator<Item = Coords<N>> + Clone {
+            moore_offsets().filter_map(move |offset| apply_rel_offset(bounds, origin, offset))
+        }
+
+        pub fn von_neumann_neighbors<const N: usize>(
+            bounds: Size<N>,
+            origin: Coords<N>,
+        ) -> impl Iterator<Item = Coords<N>> + Clone {
+            von_neumann_offsets().filter_map(move |offset| apply_rel_offset(bounds, origin, offset))
+        }
+
+        impl From<super::d2::Size> for Size<2> {
+            fn from(value: super::d2::Size) -> Self {
+                let super::d2::Size { row, col } = value;
+                Self {
+                    axes: [row.into_axis(), col.into_axis()],
+                }
+            }
+        }
+
+        impl From<Size<2>> for super::d2::Size {
+            fn from(value: Size<2>) -> Self {
+                let Size { axes: [row, col] } = value;
+                Self {
+                    row: row.into_axis(),
+                    col: col.into_axis(),
+                }
+            }
+        }
+
+        impl From<super::d2::Coords> for Coords<2> {
+            fn from(value: super::d2::Coords) -> Self {
+                let super::d2::Coords { row, col } = value;
+                Self {
+                    axes: [row.into_axis(), col.into_axis()],
+                }
+            }
+        }
+
+        impl From<Coords<2>> for super::d2::Coords {
+            fn from(value: Coords<2>) -> Self {
+                let Coords { axes: [row, col] } = value;
+                Self {
+                    row: row.into_axis(),
+                    col: col.into_axis(),
+                }
+            }
+        }
+
+        impl From<super::d2::RelativeOffset> for RelativeOffset<2> {
+            fn from(value: super::d2::RelativeOffset) -> Self {
+                let super::d2::RelativeOffset { row, col } = value;
+                Self { axes: [row, col] }
+            }
+        }
+
+        impl From<RelativeOffset<2>> for super::d2::RelativeOffset {
+            fn from(value: RelativeOffset<2>) -> Self {
+                let RelativeOffset { axes: [row, col] } = value;
+                Self { row, col }
+            }
+        }
+    }
+
+    /// Signed, unbounded counterparts of [`Coord`] and [`d2::Coords`], for puzzles whose world
+    /// isn't bounded at zero.
+    pub mod signed {
+        use std::{
+            collections::HashMap,
+            ops::{Index, Sub},
+        };
+
+        use super::{d2, Offset, PosNeg, RelativeOffset};
+
+        #[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+        pub struct Coord {
+            inner: i64,
+        }
+
+        impl Coord {
+            pub fn new(inner: i64) -> Self {
+                Self { inner }
+            }
+
+            pub fn into_inner(self) -> i64 {
+                let Self { inner } = self;
+                inner
+            }
+
+            /// Only returns `None` on `i64` overflow.
+            pub fn rel_offset(self, offset: RelativeOffset) -> Option<Self> {
+                let Self { inner } = self;
+                let RelativeOffset { value, sign } = offset;
+                let Offset { inner: value } = value;
+                let value = i64::try_from(value).ok()?;
+                (match sign {
+                    PosNeg::Positive => inner.checked_add(value),
+                    PosNeg::Negative => inner.checked_sub(value),
+                })
+                .map(|inner| Self { inner })
+            }
+        }
+
+        impl Sub for Coord {
+            type Output = RelativeOffset;
+
+            fn sub(self, rhs: Self) -> Self::Output {
+                let Self { inner: lhs } = self;
+                let Self { inner: rhs } = rhs;
+                RelativeOffset {
+                    value: Offset::new(usize::try_from(lhs.abs_diff(rhs)).unwrap()),
+                    sign: if lhs >= rhs {
+                        PosNeg::Negative
+                    } else {
+                        PosNeg::Positive
+                    },
+                }
+            }
+        }
+
+        impl<A> From<super::Coord<A>> for Coord {
+            #[track_caller]
+            fn from(value: super::Coord<A>) -> Self {
+                Self::new(i64::try_from(value.into_inner()).unwrap())
+            }
+        }
+
+        impl<A> TryFrom<Coord> for super::Coord<A> {
+            type Error = std::num::TryFromIntError;
+
+            fn try_from(value: Coord) -> Result<Self, Self::Error> {
+                usize::try_from(value.into_inner()).map(super::Coord::new)
+            }
+        }
+
+        #[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+        pub struct Coords {
+            pub row: Coord,
+            pub col: Coord,
+        }
+
+        impl Coords {
+            pub fn from_row_major(coords: (i64, i64)) -> Self {
+                let (row, col) = coords;
+                let [row, col] = [row, col].map(Coord::new);
+                Self { row, col }
+            }
+
+            pub fn into_row_major(self) -> (i64, i64) {
+                let Self { row, col } = self;
+                (row.into_inner(), col.into_inner())
+            }
+
+            /// Only returns `None` on `i64` overflow.
+            pub fn rel_offset(self, offset: d2::RelativeOffset) -> Option<Self> {
+                let Self { row, col } = self;
+                let d2::RelativeOffset {
+                    row: row_offset,
+                    col: col_offset,
+                } = offset;
+                let row = row.rel_offset(row_offset)?;
+                let col = col.rel_offset(col_offset)?;
+                Some(Self { row, col })
+            }
+        }
+
+        impl Sub for Coords {
+            type Output = d2::RelativeOffset;
+
+            fn sub(self, rhs: Self) -> Self::Output {
+                let Self {
+                    row: lhs_row,
+                    col: lhs_col,
+                } = self;
+                let Self {
+                    row: rhs_row,
+                    col: rhs_col,
+                } = rhs;
+
+                let [row, col] =
+                    [(lhs_row, rhs_row), (lhs_col, rhs_col)].map(|(lhs, rhs)| lhs - rhs);
+
+                d2::RelativeOffset { row, col }
+            }
+        }
+
+        impl From<d2::Coords> for Coords {
+            #[track_caller]
+            fn from(value: d2::Coords) -> Self {
+                let d2::Coords { row, col } = value;
+                Self {
+                    row: row.into(),
+                    col: col.into(),
+                }
+            }
+        }
+
+        /// An inclusive, axis-aligned bounding box.
+        #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+        pub struct BoundingBox {
+            pub min: Coords,
+            pub max: Coords,
+        }
+
+        impl BoundingBox {
+            pub fn from_point(coords: Coords) -> Self {
+                Self {
+                    min: coords,
+                    max: coords,
+                }
+            }
+
+            pub fn including(self, coords: Coords) -> Self {
+                let Self { min, max } = self;
+                Self {
+                    min: Coords {
+                        row: min.row.min(coords.row),
+                        col: min.col.min(coords.col),
+                    },
+                    max: Coords {
+                        row: max.row.max(coords.row),
+                        col: max.col.max(coords.col),
+                    },
+                }
+            }
+
+            pub fn contains(self, coords: Coords) -> bool {
+                let Self { min, max } = self;
+                (min.row..=max.row).contains(&coords.row)
+                    && (min.col..=max.col).contains(&coords.col)
+            }
+
+            #[track_caller]
+            pub fn size(self) -> d2::Size {
+                let Self { min, max } = self;
+                let [row, col] = [(min.row, max.row), (min.col, max.col)].map(|(min, max)| {
+                    let span = max
+                        .into_inner()
+                        .abs_diff(min.into_inner())
+                        .checked_add(1)
+                        .unwrap_or_else(|| {
+                            panic!("{self:?} spans more coordinates than fit in a `u64`")
+                        });
+                    usize::try_from(span).unwrap()
+                });
+                d2::Size::from_row_major((row, col))
+            }
+
+            /// Translates `coords` so that [`Self::min`] becomes the origin.
+            pub fn to_unsigned(self, coords: Coords) -> Option<d2::Coords> {
+                let Self { min, max: _ } = self;
+                self.contains(coords).then(|| {
+                    let [row, col] =
+                        [(min.row, coords.row), (min.col, coords.col)].map(|(min, coord)| {
+                            usize::try_from(coord.into_inner().abs_diff(min.into_inner())).unwrap()
+                        });
+                    d2::Coords::from_row_major((row, col))
+                })
+            }
+
+            /// The inverse of [`Self::to_unsigned`].
+            pub fn from_unsigned(self, coords: d2::Coords) -> Option<Coords> {
+                let Self { min, max: _ } = self;
+                self.size().contains(coords).then(|| {
+                    let origin = d2::Coords::from_row_major((0, 0));
+                    min.rel_offset(origin - coords).unwrap()
+                })
+            }
+        }
+
+        /// A grid that only stores occupied cells, and so can grow in any direction.
+        #[derive(Clone, Debug, Eq, PartialEq)]
+        pub struct SparseGrid<T> {
+            cells: HashMap<Coords, T>,
+        }
+
+        impl<T> Default for SparseGrid<T> {
+            fn default() -> Self {
+                Self {
+                    cells: HashMap::new(),
+                }
+            }
+        }
+
+        impl<T> SparseGrid<T> {
+            pub fn new() -> Self {
+                Self::default()
+            }
+
+            pub fn insert(&mut self, coords: Coords, value: T) -> Option<T> {
+                self.cells.insert(coords, value)
+            }
+
+            pub fn remove(&mut self, coords: Coords) -> Option<T> {
+                self.cells.remove(&coords)
+            }
+
+            pub fn get(&self, coords: Coords) -> Option<&T> {
+                self.cells.get(&coords)
+            }
+
+            pub fn get_mut(&mut self, coords: Coords) -> Option<&mut T> {
+                self.cells.get_mut(&coords)
+            }
+
+            pub fn len(&self) -> usize {
+                self.cells.len()
+            }
+
+            pub fn is_empty(&self) -> bool {
+                self.cells.is_empty()
+            }
+
+            /// Iterates over occupied cells in arbitrary order.
+            pub fn iter(&self) -> impl Iterator<Item = (Coords, &T)> + Clone {
+                self.cells.iter().map(|(coords, value)| (*coords, value))
+            }
+
+            /// Computes the smallest [`BoundingBox`] containing all occupied cells, or `None` if
+            /// there are none.
+            pub fn bounding_box(&self) -> Option<BoundingBox> {
+                let mut coords = self.cells.keys().copied();
+                let first = coords.next()?;
+                Some(coords.fold(BoundingBox::from_point(first), BoundingBox::including))
+            }
+        }
+
+        impl<T> Index<Coords> for SparseGrid<T> {
+            type Output = T;
+
+            #[track_caller]
+            fn index(&self, index: Coords) -> &Self::Output {
+                self.get(index)
+                    .unwrap_or_else(|| panic!("{index:?} is not occupied"))
+            }
+        }
+
+        impl<T> FromIterator<(Coords, T)> for SparseGrid<T> {
+            fn from_iter<I: IntoIterator<Item = (Coords, T)>>(iter: I) -> Self {
+                Self {
+                    cells: iter.into_iter().collect(),
+                }
+            }
+        }
+    }
+}
+
+pub mod grid {
+    use std::ops::{Index, IndexMut};
+
+    use crate::{
+        space::d2::{Coords, Size},
+        GridShapeError,
+    };
+
+    /// A rectangular grid of tiles, stored in row-major order.
+    #[derive(Clone, Debug, Eq, Hash, PartialEq)]
+    pub struct Grid<T> {
+        size: Size,
+        cells: Vec<T>,
+    }
+
+    impl<T> Grid<T> {
+        pub fn from_fn(size: Size, tile: impl FnMut(Coords) -> T) -> Self {
+            Self {
+                size,
+                cells: size.coords().map(tile).collect(),
+            }
+        }
+
+        /// Parses `lines` with [`crate::uniform_width_ascii_lines`], mapping each byte to a tile
+        /// with `tile`.
+        #[track_caller]
+        pub fn from_ascii_lines<'a>(
+            lines: impl Iterator<Item = &'a str> + Clone + 'a,
+            tile: impl FnMut(Coords, u8) -> T,
+        ) -> Self {
+            Self::try_from_ascii_lines(lines, tile).unwrap_or_else(|e| panic!("{e}"))
+        }
+
+        /// Like [`Self::from_ascii_lines`], but fails with the first [`GridShapeError`]
+        /// encountered instead of panicking.
+        pub fn try_from_ascii_lines<'a>(
+            lines: impl Iterator<Item = &'a str> + Clone + 'a,
+            mut tile: impl FnMut(Coords, u8) -> T,
+        ) -> Result<Self, GridShapeError> {
+            let mut num_rows = 0;
+            let mut num_cols = 0;
+            let mut cells = Vec::new();
+            for (row_idx, line) in crate::try_uniform_width_ascii_lines(lines).enumerate() {
+                let line = line?;
+                num_rows += 1;
+                num_cols = line.len();
+                cells.extend(
+                    line.bytes().enumerate().map(|(col_idx, byte)| {
+                        tile(Coords::from_row_major((row_idx, col_idx)), byte)
+                    }),
+                );
+            }
+            Ok(Self {
+                size: Size::from_row_major((num_rows, num_cols)),
+                cells,
+            })
+        }
+
+        pub fn size(&self) -> Size {
+            self.size
+        }
+
+        pub fn get(&self, coords: Coords) -> Option<&T> {
+            let idx = self.size.row_major_idx(coords)?;
+            Some(&self.cells[idx])
+        }
+
+        pub fn get_mut(&mut self, coords: Coords) -> Option<&mut T> {
+            let idx = self.size.row_major_idx(coords)?;
+            Some(&mut self.cells[idx])
+        }
+
+        pub fn map<U>(&self, mut f: impl FnMut(&T) -> U) -> Grid<U> {
+            let Self { size, cells } = self;
+            Grid {
+                size: *size,
+                cells: cells.iter().map(&mut f).collect(),
+            }
+        }
+
+        /// Iterates over all tiles (and their [`Coords`]) in row-major order.
+        pub fn iter(&self) -> impl Iterator<Item = (Coords, &T)> + Clone {
+            let Self { size, cells } = self;
+            size.coords().zip(cells.iter())
+        }
+
+        pub fn rows(&self) -> impl ExactSizeIterator<Item = &[T]> + Clone {
+            let Self { size, cells } = self;
+            let (num_rows, num_cols) = size.into_row_major();
+            (0..num_rows).map(move |row_idx| &cells[row_idx * num_cols..][..num_cols])
+        }
+    }
+
+    impl<T> Index<Coords> for Grid<T> {
+        type Output = T;
+
+        #[track_caller]
+        fn index(&self, index: Coords) -> &Self::Output {
+            self.get(index)
+                .unwrap_or_else(|| panic!("{index:?} is out of bounds of {:?}", self.size))
+        }
+    }
+
+    impl<T> IndexMut<Coords> for Grid<T> {
+        #[track_caller]
+        fn index_mut(&mut self, index: Coords) -> &mut Self::Output {
+            let size = self.size;
+            self.get_mut(index)
+                .unwrap_or_else(|| panic!("{index:?} is out of bounds of {size:?}"))
+        }
+    }
+
+    /// Read-only, zero-copy views of a [`Grid`] from other orientations, or of parts of it.
+    pub mod view {
+        use std::ops::Index;
+
+        use crate::space::d2::{Coords, Size};
+
+        use super::Grid;
+
+        /// Something that can be read like a [`Grid`].
+        pub trait GridView {
+            type Tile;
+
+            fn size(&self) -> Size;
+
+            fn get(&self, coords: Coords) -> Option<&Self::Tile>;
+
+            /// Iterates over all tiles (and their [`Coords`]) in row-major order.
+            fn iter(&self) -> impl Iterator<Item = (Coords, &Self::Tile)> {
+                self.size()
+                    .coords()
+                    .map(|coords| (coords, self.get(coords).unwrap()))
+            }
+
+            fn to_grid(&self) -> Grid<Self::Tile>
+            where
+                Self::Tile: Clone,
+            {
+                Grid::from_fn(self.size(), |coords| self.get(coords).unwrap().clone())
+            }
+
+            fn oriented(&self, orientation: Orientation) -> Oriented<'_, Self>
+            where
+                Self: Sized,
+            {
+                Oriented {
+                    source: self,
+                    orientation,
+                }
+            }
+
+            /// Rotates clockwise by `quarter_turns`.
+            fn rotated(&self, quarter_turns: u8) -> Oriented<'_, Self>
+            where
+                Self: Sized,
+            {
+                self.oriented(Orientation {
+                    quarter_turns,
+                    mirrored: false,
+                })
+            }
+
+            /// Mirrors left-to-right.
+            fn flipped_horizontally(&self) -> Oriented<'_, Self>
+            where
+                Self: Sized,
+            {
+                self.oriented(Orientation {
+                    quarter_turns: 0,
+                    mirrored: true,
+                })
+            }
+
+            /// Mirrors top-to-bottom.
+            fn flipped_vertically(&self) -> Oriented<'_, Self>
+            where
+                Self: Sized,
+            {
+                self.oriented(Orientation {
+                    quarter_turns: 2,
+                    mirrored: true,
+                })
+            }
+
+            /// Swaps rows with columns.
+            fn transposed(&self) -> Oriented<'_, Self>
+            where
+                Self: Sized,
+            {
+                self.oriented(Orientation {
+                    quarter_turns: 3,
+                    mirrored: true,
+                })
+            }
+
+            /// A `size`d sub-rectangle with its top-left corner at `origin`, or `None` if it
+            /// doesn't fit.
+            fn window(&self, origin: Coords, size: Size) -> Option<Window<'_, Self>>
+            where
+                Self: Sized,
+            {
+                let (origin_row, origin_col) = origin.into_row_major();
+                let (num_rows, num_cols) = size.into_row_major();
+                let (source_rows, source_cols) = self.size().into_row_major();
+                let fits = origin_row
+                    .checked_add(num_rows)
+                    .is_some_and(|end| end <= source_rows)
+                    && origin_col
+                        .checked_add(num_cols)
+                        .is_some_and(|end| end <= source_cols);
+                fits.then_some(Window {
+                    source: self,
+                    origin,
+                    size,
+                })
+            }
+        }
+
+        impl<T> GridView for Grid<T> {
+            type Tile = T;
+
+            fn size(&self) -> Size {
+                Grid::size(self)
+            }
+
+            fn get(&self, coords: Coords) -> Option<&Self::Tile> {
+                Grid::get(self, coords)
+            }
+        }
+
+        /// A transformation of a grid: an optional horizontal flip, followed by `quarter_turns`
+        /// clockwise rotations.
+        #[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+        pub struct Orientation {
+            pub quarter_turns: u8,
+            pub mirrored: bool,
+        }
+
+        /// See [`GridView::oriented`].
+        #[derive(Debug)]
+        pub struct Oriented<'a, G> {
+            source: &'a G,
+            orientation: Orientation,
+        }
+
+        impl<G> Clone for Oriented<'_, G> {
+            fn clone(&self) -> Self {
+                *self
+            }
+        }
+
+        impl<G> Copy for Oriented<'_, G> {}
+
+        impl<G> Oriented<'_, G>
+        where
+            G: GridView,
+        {
+            /// Translates `coords` in this view to the corresponding [`Coords`] in the source.
+            pub fn to_source(&self, coords: Coords) -> Option<Coords> {
+                let Self {
+                    source,
+                    orientation,
+                } = self;
+                let Orientation {
+                    quarter_turns,
+                    mirrored,
+                } = *orientation;
+
+                if !self.size().contains(coords) {
+                    return None;
+                }
+                let (row_idx, col_idx) = coords.into_row_major();
+                let (num_rows, num_cols) = source.size().into_row_major();
+                let (row_idx, col_idx) = match quarter_turns % 4 {
+                    0 => (row_idx, col_idx),
+                    1 => (num_rows - 1 - col_idx, row_idx),
+                    2 => (num_rows - 1 - row_idx, num_cols - 1 - col_idx),
+                    3 => (col_idx, num_cols - 1 - row_idx),
+                    _ => unreachable!(),
+                };
+                let col_idx = if mirrored {
+                    num_cols - 1 - col_idx
+                } else {
+                    col_idx
+                };
+                Some(Coords::from_row_major((row_idx, col_idx)))
+            }
+        }
+
+        impl<G> GridView for Oriented<'_, G>
+        where
+            G: GridView,
+        {
+            type Tile = G::Tile;
+
+            fn size(&self) -> Size {
+                let Self {
+                    source,
+                    orientation,
+                } = self;
+                let size = source.size();
+                if orientation.quarter_turns % 2 == 0 {
+                    size
+                } else {
+                    let (num_rows, num_cols) = size.into_row_major();
+                    Size::from_row_major((num_cols, num_rows))
+                }
+            }
+
+            fn get(&self, coords: Coords) -> Option<&Self::Tile> {
+                self.source.get(self.to_source(coords)?)
+            }
+        }
+
+        /// See [`GridView::window`].
+        #[derive(Debug)]
+        pub struct Window<'a, G> {
+            source: &'a G,
+            origin: Coords,
+            size: Size,
+        }
+
+        impl<G> Clone for Window<'_, G> {
+            fn clone(&self) -> Self {
+                *self
+            }
+        }
+
+        impl<G> Copy for Window<'_, G> {}
+
+        impl<G> Window<'_, G> {
+            /// Translates `coords` in this view to the corresponding [`Coords`] in the source.
+            pub fn to_source(&self, coords: Coords) -> Option<Coords> {
+                let Self {
+                    source: _,
+                    origin,
+                    size,
+                } = self;
+                size.contains(coords).then(|| {
+                    let (origin_row, origin_col) = origin.into_row_major();
+                    let (row_idx, col_idx) = coords.into_row_major();
+                    Coords::from_row_major((origin_row + row_idx, origin_col + col_idx))
+                })
+            }
+        }
+
+        impl<G> GridView for Window<'_, G>
+        where
+            G: GridView,
+        {
+            type Tile = G::Tile;
+
+            fn size(&self) -> Size {
+                self.size
+            }
+
+            fn get(&self, coords: Coords) -> Option<&Self::Tile> {
+                self.source.get(self.to_source(coords)?)
+            }
+        }
+
+        macro_rules! impl_index_for_view {
+            ($view:ident) => {
+                impl<G> Index<Coords> for $view<'_, G>
+                where
+                    G: GridView,
+                {
+                    type Output = G::Tile;
+
+                    #[track_caller]
+                    fn index(&self, index: Coords) -> &Self::Output {
+                        self.get(index).unwrap_or_else(|| {
+                            panic!("{index:?} is out of bounds of {:?}", self.size())
+                        })
+                    }
+                }
+            };
+        }
+
+        impl_index_for_view!(Oriented);
+        impl_index_for_view!(Window);
+    }
+
+    /// Parsing of grids where some tiles are actually markers for things on top of the grid
+    /// (i.e., a start position, a guard, antennae), which get replaced with a background tile.
+    pub mod markers {
+        use std::{
+            collections::BTreeMap,
+            fmt::{self, Display, Formatter},
+        };
+
+        use crate::{space::d2::Coords, GridShapeError};
+
+        use super::Grid;
+
+        /// How many times the markers of a single [`MarkerSpec`] rule may appear in a grid.
+        #[derive(Clone, Copy, Debug, Eq, PartialEq)]
+        pub enum MarkerCount {
+            ExactlyOne,
+            AtMostOne,
+            Any,
+        }
+
+        impl MarkerCount {
+            fn allows(self, count: usize) -> bool {
+                match self {
+                    Self::ExactlyOne => count == 1,
+                    Self::AtMostOne => count <= 1,
+                    Self::Any => true,
+                }
+            }
+        }
+
+        impl Display for MarkerCount {
+            fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+                f.write_str(match self {
+                    Self::ExactlyOne => "exactly one",
+                    Self::AtMostOne => "at most one",
+                    Self::Any => "any number",
+                })
+            }
+        }
+
+        /// Which bytes of a grid are markers, and how many of each are allowed.
+        ///
+        /// The count in each rule applies to all of its markers together; `exactly_one(*b"^>v<")`
+        /// means that there's one guard, facing in any direction.
+        #[derive(Clone, Debug)]
+        pub struct MarkerSpec {
+            background: u8,
+            rules: Vec<(Vec<u8>, MarkerCount)>,
+            rule_idx_by_marker: [Option<usize>; 256],
+        }
+
+        impl MarkerSpec {
+            /// Markers will be replaced with `background` in the parsed [`Grid`].
+            pub fn new(background: u8) -> Self {
+                Self {
+                    background,
+                    rules: Vec::new(),
+                    rule_idx_by_marker: [None; 256],
+                }
+            }
+
+            pub fn exactly_one(self, markers: impl IntoIterator<Item = u8>) -> Self {
+                self.with_rule(markers, MarkerCount::ExactlyOne)
+            }
+
+            pub fn at_most_one(self, markers: impl IntoIterator<Item = u8>) -> Self {
+                self.with_rule(markers, MarkerCount::AtMostOne)
+            }
+
+            pub fn any_number(self, markers: impl IntoIterator<Item = u8>) -> Self {
+                self.with_rule(markers, MarkerCount::Any)
+            }
+
+            #[track_caller]
+            pub fn with_rule(
+                mut self,
+                markers: impl IntoIterator<Item = u8>,
+                count: MarkerCount,
+            ) -> Self {
+                let Self {
+                    background,
+                    rules,
+                    rule_idx_by_marker,
+                } = &mut self;
+                let rule_idx = rules.len();
+                let markers = markers
+                    .into_iter()
+                    .inspect(|&marker| {
+                        assert_ne!(
+                            marker,
+                            *background,
+                            "{:?} is the background tile, so it can't be a marker",
+                            char::from(marker)
+                        );
+                        assert!(
+                            rule_idx_by_marker[usize::from(marker)]
+                                .replace(rule_idx)
+                                .is_none(),
+                            "{:?} is already a marker",
+                            char::from(marker)
+                        );
+                    })
+                    .collect();
+                rules.push((markers, count));
+                self
+            }
+
+            /// Like [`Grid::from_ascii_lines`], but markers are collected into [`Markers`] and
+            /// `tile` sees the background tile in their place.
+            #[track_caller]
+            pub fn parse<'a, T>(
+                &self,
+                lines: impl Iterator<Item = &'a str> + Clone + 'a,
+                tile: impl FnMut(Coords, u8) -> T,
+            ) -> (Grid<T>, Markers) {
+                self.try_parse(lines, tile)
+                    .unwrap_or_else(|e| panic!("{e}"))
+            }
+
+            /// Like [`Self::parse`], but fails with a [`MarkerError`] instead of panicking.
+            pub fn try_parse<'a, T>(
+                &self,
+                lines: impl Iterator<Item = &'a str> + Clone + 'a,
+                mut tile: impl FnMut(Coords, u8) -> T,
+            ) -> Result<(Grid<T>, Markers), MarkerError> {
+                let Self {
+                    background,
+                    rules,
+                    rule_idx_by_marker,
+                } = self;
+
+                let mut positions = BTreeMap::<_, Vec<_>>::new();
+                let grid = Grid::try_from_ascii_lines(lines, |coords, byte| {
+                    if rule_idx_by_marker[usize::from(byte)].is_some() {
+                        positions.entry(byte).or_default().push(coords);
+                        tile(coords, *background)
+                    } else {
+                        tile(coords, byte)
+                    }
+                })
+                .map_err(MarkerError::Shape)?;
+
+                for (markers, expected) in rules {
+                    let found = markers
+                        .iter()
+                        .flat_map(|marker| {
+                            positions
+                                .get(marker)
+                                .into_iter()
+                                .flatten()
+                                .map(|&coords| (*marker, coords))
+                        })
+                        .collect::<Vec<_>>();
+                    if !expected.allows(found.len()) {
+                        return Err(MarkerError::Count {
+                            markers: markers.clone(),
+                            expected: *expected,
+                            found,
+                        });
+                    }
+                }
+
+                Ok((grid, Markers { positions }))
+            }
+        }
+
+        /// The positions of the markers found by [`MarkerSpec::parse`].
+        #[derive(Clone, Debug, Default, Eq, PartialEq)]
+        pub struct Markers {
+            positions: BTreeMap<u8, Vec<Coords>>,
+        }
+
+        impl Markers {
+            /// The positions of `marker`, in row-major order.
+            pub fn get(&self, marker: u8) -> &[Coords] {
+                let Self { positions } = self;
+                positions.get(&marker).map_or(&[], |coords| coords)
+            }
+
+            /// Iterates over each marker that was found, along with its positions.
+            pub fn by_marker(&self) -> impl Iterator<Item = (u8, &[Coords])> + Clone {
+                let Self { positions } = self;
+                positions
+                    .iter()
+                    .map(|(&marker, coords)| (marker, coords.as_slice()))
+            }
+
+            pub fn iter(&self) -> impl Iterator<Item = (u8, Coords)> + Clone + '_ {
+                self.by_marker()
+                    .flat_map(|(marker, coords)| coords.iter().map(move |&coords| (marker, coords)))
+            }
+        }
+
+        #[derive(Clone, Debug, Eq, PartialEq)]
+        pub enum MarkerError {
+            Shape(GridShapeError),
+            Count {
+                markers: Vec<u8>,
+                expected: MarkerCount,
+                found: Vec<(u8, Coords)>,
+            },
+        }
+
+        impl Display for MarkerError {
+            fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+                match self {
+                    Self::Shape(e) => Display::fmt(e, f),
+                    Self::Count {
+                        markers,
+                        expected,
+                        found,
+                    } => {
+                        write!(f, "expected {expected} of ")?;
+                        for (idx, marker) in markers.iter().enumerate() {
+                            if idx != 0 {
+                                f.write_str(", ")?;
+                            }
+                            write!(f, "{:?}", char::from(*marker))?;
+                        }
+                        if found.is_empty() {
+                            return f.write_str(", but found none");
+                        }
+                        write!(f, ", but found {}", found.len())?;
+                        for (idx, (marker, coords)) in found.iter().enumerate() {
+                            write!(
+                                f,
+                                "{}{:?} at {:?}",
+                                if idx == 0 { ": " } else { ", " },
+                                char::from(*marker),
+                                coords.into_row_major()
+                            )?;
+                        }
+                        Ok(())
+                    }
+                }
+            }
+        }
+
+        impl std::error::Error for MarkerError {}
+    }
+}
+
+pub mod search_direction {
+    use crate::space::d2::{Coords, Size};
+
+    #[derive(Clone, Copy, Debug, Eq, PartialEq, strum::EnumIter)]
+    pub enum Sign {
+        Neutral,
+        Negative,
+        Positive,
+    }
+
+    #[derive(Clone, Copy, Debug, Eq, PartialEq)]
+    pub struct SearchDirection {
+        pub horizontal: Sign,
+        pub vertical: Sign,
+    }
+
+    impl SearchDirection {
+        /// Steps `offset` times from `origin`, returning `None` if that leaves `bounds`.
+        pub fn to_2d_offsets(&self, origin: Coords, bounds: Size, offset: usize) -> Option<Coords> {
+            let offset = self.to_rel_offset().checked_mul(offset).unwrap();
+            crate::space::d2::apply_rel_offset(bounds, origin, offset)
+        }
+
+        /// The unit step in this direction.
+        pub fn to_rel_offset(&self) -> crate::space::d2::RelativeOffset {
+            let &Self {
+                horizontal,
+                vertical,
+            } = self;
+
+            let convert = |sign| {
+                let (value, sign) = match sign {
+                    Sign::Positive => (1, crate::space::PosNeg::Positive),
+                    Sign::Neutral => (0, crate::space::PosNeg::Positive),
+                    Sign::Negative => (1, crate::space::PosNeg::Negative),
+                };
+                let value = crate::space::Offset::new(value);
+                crate::space::RelativeOffset { value, sign }
+            };
+
+            crate::space::d2::RelativeOffset {
+                row: convert(vertical),
+                col: convert(horizontal),
+            }
+        }
+    }
+}
+
+pub mod direction {
+    use crate::{
+        search_direction::{SearchDirection, Sign},
+        space::d2::{RelativeOffset, MOORE_STENCIL, VON_NEUMANN_STENCIL},
+    };
+
+    /// A cardinal direction, where north is up (i.e., towards row 0).
+    #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq, strum::EnumIter)]
+    pub enum Direction {
+        North,
+        East,
+        South,
+        West,
+    }
+
+    impl Direction {
+        const CLOCKWISE: [Self; 4] = [Self::North, Self::East, Self::South, Self::West];
+
+        fn clockwise_idx(self) -> usize {
+            Self::CLOCKWISE.iter().position(|d| *d == self).unwrap()
+        }
+
+        /// Rotates clockwise by `quarter_turns`, which may be negative.
+        pub fn rotate_by(self, quarter_turns: isize) -> Self {
+            let idx = self
+                .clockwise_idx()
+                .checked_add_signed(quarter_turns.rem_euclid(4))
+                .unwrap();
+            Self::CLOCKWISE[idx % 4]
+        }
+
+        pub fn turn_right(self) -> Self {
+            self.rotate_by(1)
+        }
+
+        pub fn turn_left(self) -> Self {
+            self.rotate_by(-1)
+        }
+
+        pub fn reverse(self) -> Self {
+            self.rotate_by(2)
+        }
+
+        pub fn to_rel_offset(self) -> RelativeOffset {
+            VON_NEUMANN_STENCIL[self.clockwise_idx()]
+        }
+
+        pub fn to_search_direction(self) -> SearchDirection {
+            let (horizontal, vertical) = match self {
+                Self::North => (Sign::Neutral, Sign::Negative),
+                Self::East => (Sign::Positive, Sign::Neutral),
+                Self::South => (Sign::Neutral, Sign::Positive),
+                Self::West => (Sign::Negative, Sign::Neutral),
+            };
+            SearchDirection {
+                horizontal,
+                vertical,
+            }
+        }
+
+        /// Accepts arrows (`^>v<`), `UDLR` and `NESW`.
+        pub fn from_char(c: char) -> Option<Self> {
+            Some(match c {
+                '^' | 'U' | 'N' => Self::North,
+                '>' | 'R' | 'E' => Self::East,
+                'v' | 'D' | 'S' => Self::South,
+                '<' | 'L' | 'W' => Self::West,
+                _ => return None,
+            })
+        }
+
+        pub fn to_arrow(self) -> char {
+            match self {
+                Self::North => '^',
+                Self::East => '>',
+                Self::South => 'v',
+                Self::West => '<',
+            }
+        }
+    }
+
+    impl From<Direction> for RelativeOffset {
+        fn from(value: Direction) -> Self {
+            value.to_rel_offset()
+        }
+    }
+
+    impl From<Direction> for SearchDirection {
+        fn from(value: Direction) -> Self {
+            value.to_search_direction()
+        }
+    }
+
+    /// A cardinal or intercardinal direction, where north is up (i.e., towards row 0).
+    #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq, strum::EnumIter)]
+    pub enum Direction8 {
+        North,
+        NorthEast,
+        East,
+        SouthEast,
+        South,
+        SouthWest,
+        West,
+        NorthWest,
+    }
+
+    impl Direction8 {
+        const CLOCKWISE: [Self; 8] = [
+            Self::North,
+            Self::NorthEast,
+            Self::East,
+            Self::SouthEast,
+            Self::South,
+            Self::SouthWest,
+            Self::West,
+            Self::NorthWest,
+        ];
+
+        fn clockwise_idx(self) -> usize {
+            Self::CLOCKWISE.iter().position(|d| *d == self).unwrap()
+        }
+
+        /// Rotates clockwise by `eighth_turns`, which may be negative.
+        pub fn rotate_by(self, eighth_turns: isize) -> Self {
+            let idx = self
+                .clockwise_idx()
+                .checked_add_signed(eighth_turns.rem_euclid(8))
+                .unwrap();
+            Self::CLOCKWISE[idx % 8]
+        }
+
+        pub fn turn_right(self) -> Self {
+            self.rotate_by(1)
+        }
+
+        pub fn turn_left(self) -> Self {
+            self.rotate_by(-1)
+        }
+
+        pub fn reverse(self) -> Self {
+            self.rotate_by(4)
+        }
+
+        pub fn to_rel_offset(self) -> RelativeOffset {
+            MOORE_STENCIL[self.clockwise_idx()]
+        }
+
+        pub fn to_search_direction(self) -> SearchDirection {
+            let (horizontal, vertical) = match self {
+                Self::North => (Sign::Neutral, Sign::Negative),
+                Self::NorthEast => (Sign::Positive, Sign::Negative),
+                Self::East => (Sign::Positive, Sign::Neutral),
+                Self::SouthEast => (Sign::Positive, Sign::Positive),
+                Self::South => (Sign::Neutral, Sign::Positive),
+                Self::SouthWest => (Sign::Negative, Sign::Positive),
+                Self::West => (Sign::Negative, Sign::Neutral),
+                Self::NorthWest => (Sign::Negative, Sign::Negative),
+            };
+            SearchDirection {
+                horizontal,
+                vertical,
+            }
+        }
+
+        /// Accepts the same characters as [`Direction::from_char`].
+        pub fn from_char(c: char) -> Option<Self> {
+            Direction::from_char(c).map(Self::from)
+        }
+    }
+
+    impl From<Direction> for Direction8 {
+        fn from(value: Direction) -> Self {
+            match value {
+                Direction::North => Self::North,
+                Direction::East => Self::East,
+                Direction::South => Self::South,
+                Direction::West => Self::West,
+            }
+        }
+    }
+
+    impl From<Direction8> for RelativeOffset {
+        fn from(value: Direction8) -> Self {
+            value.to_rel_offset()
+        }
+    }
+
+    impl From<Direction8> for SearchDirection {
+        fn from(value: Direction8) -> Self {
+            value.to_search_direction()
+        }
+    }
+}
+
+pub mod pattern {
+    pub use crate::grid::view::Orientation;
+    use crate::{
+        grid::{view::GridView as _, Grid},
+        space::d2::{Coords, Size},
+    };
+
+    /// A small rectangular template to search for in a [`Grid`] of bytes. `None` cells are
+    /// wildcards.
+    #[derive(Clone, Debug, Eq, Hash, PartialEq)]
+    pub struct Pattern {
+        cells: Grid<Option<u8>>,
+    }
+
+    impl Pattern {
+        /// Parses `template` as a grid, where `.` is a wildcard.
+        ///
+        /// # Panics
+        ///
+        /// If `template` is empty, since an empty pattern would match everywhere, including
+        /// past the edges of the grid.
+        #[track_caller]
+        pub fn parse(template: &str) -> Self {
+            let cells = Grid::from_ascii_lines(template.lines(), |_coords, byte| match byte {
+                b'.' => None,
+                byte => Some(byte),
+            });
+            let (num_rows, num_cols) = cells.size().into_row_major();
+            assert!(
+                num_rows > 0 && num_cols > 0,
+                "can't search for an empty pattern"
+            );
+            Self { cells }
+        }
+
+        pub fn size(&self) -> Size {
+            self.cells.size()
+        }
+
+        pub fn oriented(&self, orientation: Orientation) -> Self {
+            let Self { cells } = self;
+            let cells = cells.oriented(orientation).to_grid();
+            Self { cells }
+        }
+
+        /// All distinct orientations of this pattern permitted by `symmetry`. When several
+        /// orientations produce the same pattern (e.g., for symmetric patterns), only the first
+        /// is kept, so that matches aren't counted multiple times.
+        pub fn orientations(&self, symmetry: Symmetry) -> Vec<(Orientation, Self)> {
+            let mirrorings: &[bool] = match symmetry {
+                Symmetry::None => return vec![(Orientation::default(), self.clone())],
+                Symmetry::Rotations => &[false],
+                Symmetry::RotationsAndReflections => &[false, true],
+            };
+            let mut orientations = Vec::<(Orientation, Self)>::new();
+            for &mirrored in mirrorings {
+                for quarter_turns in 0..4 {
+                    let orientation = Orientation {
+                        quarter_turns,
+                        mirrored,
+                    };
+                    let oriented = self.oriented(orientation);
+                    if orientations
+                        .iter()
+                        .all(|(_, existing)| *existing != oriented)
+                    {
+                        orientations.push((orientation, oriented));
+                    }
+                }
+            }
+            orientations
+        }
+
+        /// Checks whether this pattern matches `grid` with its top-left corner at `origin`.
+        pub fn matches_at(&self, grid: &Grid<u8>, origin: Coords) -> bool {
+            let (origin_row, origin_col) = origin.into_row_major();
+            self.cells.iter().all(|(coords, expected)| {
+                let (row_idx, col_idx) = coords.into_row_major();
+                let coords = Coords::from_row_major((origin_row + row_idx, origin_col + col_idx));
+                expected.is_none_or(|expected| grid.get(coords) == Some(&expected))
+            })
+        }
+    }
+
+    #[derive(Clone, Copy, Debug, Eq, PartialEq)]
+    pub enum Symmetry {
+        None,
+        Rotations,
+        RotationsAndReflections,
+    }
+
+    #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+    pub struct PatternMatch {
+        /// The top-left corner of the oriented pattern.
+        pub origin: Coords,
+        pub orientation: Orientation,
+    }
+
+    /// Finds every placement of every orientation of `pattern` (per `symmetry`) in `grid`.
+    pub fn find_matches<'a>(
+        grid: &'a Grid<u8>,
+        pattern: &Pattern,
+        symmetry: Symmetry,
+    ) -> impl Iterator<Item = PatternMatch> + 'a {
+        pattern
+            .orientations(symmetry)
+            .into_iter()
+            .flat_map(move |(orientation, oriented)| {
+                let (num_rows, num_cols) = grid.size().into_row_major();
+                let (pattern_rows, pattern_cols) = oriented.size().into_row_major();
+                let origins = Size::from_row_major((
+                    (num_rows + 1).saturating_sub(pattern_rows),
+                    (num_cols + 1).saturating_sub(pattern_cols),
+                ))
+                .coords();
+                origins
+                    .filter(move |origin| oriented.matches_at(grid, *origin))
+                    .map(move |origin| PatternMatch {
+                        origin,
+                        orientation,
+                    })
+            })
+    }
+}
+
+pub mod word_search {
+    use aho_corasick::AhoCorasick;
+    use strum::IntoEnumIterator as _;
+
+    use crate::{
+        direction::Direction8,
+        grid::Grid,
+        search_direction::SearchDirection,
+        space::d2::{checked_apply_rel_offset, ray, Coords},
+    };
+
+    /// Searches a [`Grid`] of letters for any of a fixed set of words, in all eight directions.
+    #[derive(Clone, Debug)]
+    pub struct WordSearch {
+        automaton: AhoCorasick,
+        words: Vec<Vec<u8>>,
+    }
+
+    #[derive(Clone, Debug, Eq, PartialEq)]
+    pub struct WordMatch<'a> {
+        pub word: &'a [u8],
+        pub start: Coords,
+        pub direction: SearchDirection,
+    }
+
+    impl WordSearch {
+        /// # Panics
+        ///
+        /// If any of `words` is empty.
+        #[track_caller]
+        pub fn new<W>(words: impl IntoIterator<Item = W>) -> Self
+        where
+            W: AsRef<[u8]>,
+        {
+            let words = words
+                .into_iter()
+                .map(|word| word.as_ref().to_owned())
+                .collect::<Vec<_>>();
+            assert!(
+                words.iter().all(|word| !word.is_empty()),
+                "can't search for an empty word"
+            );
+            let automaton = AhoCorasick::new(&words).unwrap();
+            Self { automaton, words }
+        }
+
+        /// Finds every occurrence of every word, scanning each straight line through `grid` once
+        /// per direction.
+        pub fn find_all<'a>(&'a self, grid: &Grid<u8>) -> Vec<WordMatch<'a>> {
+            let Self { automaton, words } = self;
+            let size = grid.size();
+
+            let mut matches = Vec::new();
+            let mut line_coords = Vec::new();
+            let mut line_letters = Vec::new();
+            for direction in Direction8::iter() {
+                let step = direction.to_rel_offset();
+                let line_starts = size
+                    .coords()
+                    .filter(|coords| checked_apply_rel_offset(size, *coords, -step).is_none());
+                for line_start in line_starts {
+                    line_coords.clear();
+                    line_coords.extend(ray(size, line_start, step));
+                    line_letters.clear();
+                    line_letters.extend(line_coords.iter().map(|coords| grid[*coords]));
+
+                    matches.extend(automaton.find_overlapping_iter(&line_letters).map(|m| {
+                        WordMatch {
+                            word: &words[m.pattern()],
+                            start: line_coords[m.start()],
+                            direction: direction.to_search_direction(),
+                        }
+                    }));
+                }
+            }
+            matches
+        }
+    }
+}
+
+pub mod search {
+    use std::{
+        cmp::Reverse,
+        collections::{hash_map::Entry, BinaryHeap, HashMap, HashSet, VecDeque},
+        hash::Hash,
+    };
+
+    use crate::{
+        direction::Direction,
+        space::d2::{checked_apply_rel_offset, neighbors4, Coords, Size},
+    };
+
+    /// How many predecessors a search records for each state.
+    #[derive(Clone, Copy, Debug, Eq, PartialEq)]
+    pub enum Predecessors {
+        /// Only the first predecessor found on a shortest path. Searches stop as soon as a goal
+        /// is reached.
+        First,
+        /// Every predecessor on any shortest path. Searches continue until every goal at the
+        /// shortest distance has been reached.
+        All,
+    }
+
+    #[derive(Clone, Debug)]
+    pub struct SearchResult<S> {
+        distances: HashMap<S, u64>,
+        predecessors: HashMap<S, Vec<S>>,
+        goals: Vec<S>,
+    }
+
+    impl<S> SearchResult<S>
+    where
+        S: Clone + Eq + Hash,
+    {
+        fn new() -> Self {
+            Self {
+                distances: HashMap::new(),
+                predecessors: HashMap::new(),
+                goals: Vec::new(),
+            }
+        }
+
+        /// Records another shortest-path predecessor of `state`, which must already have been
+        /// reached.
+        fn add_predecessor(&mut self, state: &S, predecessor: &S) {
+            // NOTE: Start states are the only reached states without an entry, and they should
+            // keep having no predecessors, even when a zero-cost step leads back to them.
+            if let Some(predecessors) = self.predecessors.get_mut(state) {
+                predecessors.push(predecessor.clone());
+            }
+        }
+
+        /// The best known distance to `state`. This is only guaranteed to be the shortest
+        /// distance for states that were expanded before the search finished.
+        pub fn distance(&self, state: &S) -> Option<u64> {
+            self.distances.get(state).copied()
+        }
+
+        pub fn distances(&self) -> &HashMap<S, u64> {
+            &self.distances
+        }
+
+        /// Goal states reached at the shortest distance, in the order they were reached.
+        pub fn goals(&self) -> &[S] {
+            &self.goals
+        }
+
+        pub fn predecessors(&self, state: &S) -> &[S] {
+            self.predecessors.get(state).map_or(&[], Vec::as_slice)
+        }
+
+        /// A shortest path from a start state to `target`, inclusive.
+        pub fn path_to(&self, target: &S) -> Option<Vec<S>> {
+            self.distances.get(target)?;
+            let mut path = vec![target.clone()];
+            while let Some(predecessor) = self.predecessors(path.last().unwrap()).first() {
+                path.push(predecessor.clone());
+            }
+            path.reverse();
+            Some(path)
+        }
+
+        /// A shortest path to the first goal reached, if any.
+        pub fn path(&self) -> Option<Vec<S>> {
+            self.path_to(self.goals.first()?)
+        }
+
+        /// Every state on any shortest path to any goal. Only complete with
+        /// [`Predecessors::All`].
+        pub fn states_on_shortest_paths(&self) -> HashSet<S> {
+            let mut visited = HashSet::new();
+            let mut to_visit = self.goals.clone();
+            while let Some(state) = to_visit.pop() {
+                if visited.insert(state.clone()) {
+                    to_visit.extend(self.predecessors(&state).iter().cloned());
+                }
+            }
+            visited
+        }
+    }
+
+    /// Breadth-first search, for when every step costs the same.
+    pub fn bfs<S, I>(
+        starts: impl IntoIterator<Item = S>,
+        mut successors: impl FnMut(&S) -> I,
+        mut is_goal: impl FnMut(&S) -> bool,
+        predecessors: Predecessors,
+    ) -> SearchResult<S>
+    where
+        S: Clone + Eq + Hash,
+        I: IntoIterator<Item = S>,
+    {
+        let mut result = SearchResult::new();
+        let mut queue = VecDeque::new();
+        for start in starts {
+            if result.distances.insert(start.clone(), 0).is_none() {
+                queue.push_back((start, 0));
+            }
+        }
+
+        let mut goal_distance = None;
+        while let Some((state, distance)) = queue.pop_front() {
+            if goal_distance.is_some_and(|goal_distance| distance > goal_distance) {
+                break;
+            }
+            if is_goal(&state) {
+                goal_distance = Some(distance);
+                result.goals.push(state);
+                match predecessors {
+                    Predecessors::First => break,
+                    Predecessors::All => continue,
+                }
+            }
+
+            let next_distance = distance + 1;
+            for next in successors(&state) {
+                match result.distances.entry(next.clone()) {
+                    Entry::Occupied(entry) => {
+                        if *entry.get() == next_distance && predecessors == Predecessors::All {
+                            result.add_predecessor(&next, &state);
+                        }
+                    }
+                    Entry::Vacant(entry) => {
+                        entry.insert(next_distance);
+                        result
+                            .predecessors
+                            .insert(next.clone(), vec![state.clone()]);
+                        queue.push_back((next, next_distance));
+                    }
+                }
+            }
+        }
+        result
+    }
+
+    /// Dijkstra's algorithm; i.e., [`astar`] without a heuristic.
+    pub fn dijkstra<S, I>(
+        starts: impl IntoIterator<Item = S>,
+        successors: impl FnMut(&S) -> I,
+        is_goal: impl FnMut(&S) -> bool,
+        predecessors: Predecessors,
+    ) -> SearchResult<S>
+    where
+        S: Clone + Eq + Hash,
+        I: IntoIterator<Item = (S, u64)>,
+    {
+        astar(starts, successors, |_state| 0, is_goal, predecessors)
+    }
+
+    /// A* search. `successors` yields each next state with the cost of stepping to it, and
+    /// `heuristic` must never overestimate the remaining distance to a goal.
+    pub fn astar<S, I>(
+        starts: impl IntoIterator<Item = S>,
+        mut successors: impl FnMut(&S) -> I,
+        mut heuristic: impl FnMut(&S) -> u64,
+        mut is_goal: impl FnMut(&S) -> bool,
+        predecessors: Predecessors,
+    ) -> SearchResult<S>
+    where
+        S: Clone + Eq + Hash,
+        I: IntoIterator<Item = (S, u64)>,
+    {
+        let mut result = SearchResult::new();
+        let mut queue = PriorityQueue::new();
+        let mut enqueue = |queue: &mut PriorityQueue<S>, state: S, distance: u64| {
+            let estimate = distance.checked_add(heuristic(&state)).unwrap();
+            queue.push(state, estimate, distance);
+        };
+
+        for start in starts {
+            if result.distances.insert(start.clone(), 0).is_none() {
+                enqueue(&mut queue, start, 0);
+            }
+        }
+
+        let mut goal_distance = None;
+        while let Some((state, estimate, distance)) = queue.pop() {
+            if goal_distance.is_some_and(|goal_distance| estimate > goal_distance) {
+                break;
+            }
+            if result.distances[&state] < distance {
+                continue;
+            }
+            if is_goal(&state) {
+                goal_distance = Some(distance);
+                result.goals.push(state);
+                match predecessors {
+                    Predecessors::First => break,
+                    Predecessors::All => continue,
+                }
+            }
+
+            for (next, cost) in successors(&state) {
+                let next_distance = distance.checked_add(cost).unwrap();
+                match result.distances.entry(next.clone()) {
+                    Entry::Occupied(mut entry) => {
+                        if next_distance < *entry.get() {
+                            entry.insert(next_distance);
+                            result
+                                .predecessors
+                                .insert(next.clone(), vec![state.clone()]);
+                            enqueue(&mut queue, next, next_distance);
+                        } else if next_distance == *entry.get() && predecessors == Predecessors::All
+                        {
+                            result.add_predecessor(&next, &state);
+                        }
+                    }
+                    Entry::Vacant(entry) => {
+                        entry.insert(next_distance);
+                        result
+                            .predecessors
+                            .insert(next.clone(), vec![state.clone()]);
+                        enqueue(&mut queue, next, next_distance);
+                    }
+                }
+            }
+        }
+        result
+    }
+
+    /// A min-heap of states by estimate, then distance. States live outside of the heap, so that
+    /// they needn't be `Ord`.
+    struct PriorityQueue<S> {
+        heap: BinaryHeap<Reverse<(u64, u64, usize)>>,
+        states: Vec<Option<S>>,
+    }
+
+    impl<S> PriorityQueue<S> {
+        fn new() -> Self {
+            Self {
+                heap: BinaryHeap::new(),
+                states: Vec::new(),
+            }
+        }
+
+        fn push(&mut self, state: S, estimate: u64, distance: u64) {
+            let Self { heap, states } = self;
+            heap.push(Reverse((estimate, distance, states.len())));
+            states.push(Some(state));
+        }
+
+        fn pop(&mut self) -> Option<(S, u64, u64)> {
+            let Self { heap, states } = self;
+            let Reverse((estimate, distance, state_idx)) = heap.pop()?;
+            let state = states[state_idx].take().unwrap();
+            Some((state, estimate, distance))
+        }
+    }
+
+    /// Successors for [`bfs`] over a grid where movement is orthogonal and `passable` decides
+    /// which cells may be entered.
+    pub fn grid_successors(
+        bounds: Size,
+        coords: Coords,
+        mut passable: impl FnMut(Coords) -> bool,
+    ) -> impl Iterator<Item = Coords> {
+        neighbors4(bounds, coords)
+            .map(|(_direction, coords)| coords)
+            .filter(move |coords| passable(*coords))
+    }
+
+    /// Successors for [`dijkstra`] or [`astar`] over a grid where turning in place by 90° costs
+    /// `turn_cost`, and stepping forward into a `passable` cell costs `step_cost`.
+    pub fn oriented_grid_successors(
+        bounds: Size,
+        state: (Coords, Direction),
+        step_cost: u64,
+        turn_cost: u64,
+        mut passable: impl FnMut(Coords) -> bool,
+    ) -> impl Iterator<Item = ((Coords, Direction), u64)> {
+        let (coords, direction) = state;
+        let step = checked_apply_rel_offset(bounds, coords, direction.to_rel_offset())
+            .filter(|next| passable(*next))
+            .map(|next| ((next, direction), step_cost));
+        let turns = [direction.turn_left(), direction.turn_right()]
+            .map(|direction| ((coords, direction), turn_cost));
+        step.into_iter().chain(turns)
+    }
+}
+
+pub mod regions {
+    use crate::{
+        grid::Grid,
+        space::d2::{apply_rel_offset, Coords, RelativeOffset, VON_NEUMANN_STENCIL},
+    };
+
+    /// Connected components of a [`Grid`], where orthogonally adjacent cells are connected if
+    /// they satisfy some equivalence.
+    #[derive(Clone, Debug)]
+    pub struct Regions {
+        labels: Grid<usize>,
+        regions: Vec<Region>,
+    }
+
+    #[derive(Clone, Debug, Eq, PartialEq)]
+    pub struct Region {
+        /// This region's index into [`Regions::regions`], and its value in
+        /// [`Regions::labels`].
+        pub label: usize,
+        /// The first cell of this region in row-major order.
+        pub representative: Coords,
+        pub area: usize,
+        pub perimeter: usize,
+        /// The number of straight edges making up the perimeter, counted via corners.
+        pub sides: usize,
+    }
+
+    impl Regions {
+        /// Labels every cell of `grid` with its region. `same_region` should be an equivalence
+        /// relation.
+        pub fn find<T>(grid: &Grid<T>, mut same_region: impl FnMut(&T, &T) -> bool) -> Self {
+            // NOTE: `labels` doubles as the set of visited cells, and `found` is shared between
+            // regions, so that this is linear in the number of cells.
+            let mut labels = grid.map(|_| None);
+            let mut num_regions = 0;
+            let mut found = Vec::new();
+            for start in grid.size().coords() {
+                if labels[start].is_some() {
+                    continue;
+                }
+                found.clear();
+                fill(
+                    grid,
+                    start,
+                    num_regions,
+                    &mut labels,
+                    &mut found,
+                    &mut same_region,
+                );
+                num_regions += 1;
+            }
+            let labels = labels.map(|label| label.unwrap());
+
+            let mut regions = Vec::<Region>::with_capacity(num_regions);
+            for (coords, &label) in labels.iter() {
+                if label == regions.len() {
+                    regions.push(Region {
+                        label,
+                        representative: coords,
+                        area: 0,
+                        perimeter: 0,
+                        sides: 0,
+                    });
+                }
+                let same_label = |offset| {
+                    apply_rel_offset(labels.size(), coords, offset)
+                        .is_some_and(|neighbor| labels[neighbor] == label)
+                };
+
+                let region = &mut regions[label];
+                region.area += 1;
+                region.perimeter += VON_NEUMANN_STENCIL
+                    .iter()
+                    .filter(|offset| !same_label(**offset))
+                    .count();
+                region.sides += corner_pairs()
+                    .filter(|&(first, second)| {
+                        let diagonal = first.checked_add(second).unwrap();
+                        match (same_label(first), same_label(second)) {
+                            (false, false) => true,
+                            (true, true) => !same_label(diagonal),
+                            _ => false,
+                        }
+                    })
+                    .count();
+            }
+
+            Self { labels, regions }
+        }
+
+        /// Each cell's region label.
+        pub fn labels(&self) -> &Grid<usize> {
+            &self.labels
+        }
+
+        pub fn regions(&self) -> &[Region] {
+            &self.regions
+        }
+
+        pub fn region_at(&self, coords: Coords) -> Option<&Region> {
+            let label = *self.labels.get(coords)?;
+            Some(&self.regions[label])
+        }
+    }
+
+    /// Each pair of orthogonal directions that meet at a corner, clockwise from up-right.
+    fn corner_pairs() -> impl Iterator<Item = (RelativeOffset, RelativeOffset)> {
+        let [up, right, down, left] = VON_NEUMANN_STENCIL;
+        [(up, right), (right, down), (down, left), (left, up)].into_iter()
+    }
+
+    /// All cells orthogonally connected to `start` via `same_region`, in the order they were
+    /// found.
+    pub fn flood_fill<T>(
+        grid: &Grid<T>,
+        start: Coords,
+        mut same_region: impl FnMut(&T, &T) -> bool,
+    ) -> Vec<Coords> {
+        let mut labels = grid.map(|_| None);
+        let mut found = Vec::new();
+        fill(grid, start, 0, &mut labels, &mut found, &mut same_region);
+        found
+    }
+
+    /// Labels `start`, which must be unlabelled, and every unlabelled cell orthogonally
+    /// connected to it via `same_region` with `label`, appending them to `found` in the order
+    /// they were found.
+    fn fill<T>(
+        grid: &Grid<T>,
+        start: Coords,
+        label: usize,
+        labels: &mut Grid<Option<usize>>,
+        found: &mut Vec<Coords>,
+        same_region: &mut impl FnMut(&T, &T) -> bool,
+    ) {
+        labels[start] = Some(label);
+        let mut next_idx = found.len();
+        found.push(start);
+        while let Some(&coords) = found.get(next_idx) {
+            next_idx += 1;
+            for offset in VON_NEUMANN_STENCIL {
+                let Some(neighbor) = apply_rel_offset(grid.size(), coords, offset) else {
+                    continue;
+                };
+                if labels[neighbor].is_none() && same_region(&grid[coords], &grid[neighbor]) {
+                    labels[neighbor] = Some(label);
+                    found.push(neighbor);
+                }
+            }
+        }
+    }
+}
+
+/// Drivers for simulations whose states eventually repeat.
+pub mod simulate {
+    use std::{collections::HashMap, hash::Hash};
+
+    /// States at indices `start..start + len` repeat forever.
+    #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+    pub struct Cycle {
+        /// The index of the first state in the cycle, where the initial state is at index 0.
+        pub start: usize,
+        pub len: usize,
+    }
+
+    #[derive(Clone, Debug, Eq, PartialEq)]
+    pub enum Outcome<S> {
+        /// `step` returned `None` after `steps` successful steps, ending at `state`.
+        Halted {
+            steps: usize,
+            state: S,
+        },
+        Cycled(Cycle),
+    }
+
+    /// Finds a cycle by remembering every state seen. Uses memory proportional to
+    /// `start + len`, but calls `step` only `start + len` times.
+    pub fn find_cycle_hashed<S>(initial: S, step: impl FnMut(&S) -> Option<S>) -> Outcome<S>
+    where
+        S: Clone + Eq + Hash,
+    {
+        let mut history = History::new(initial);
+        history.run(step, |_| false)
+    }
+
+    /// Finds a cycle with Brent's algorithm, which only keeps two states in memory at a time,
+    /// but may call `step` several times more than [`find_cycle_hashed`].
+    pub fn find_cycle_brent<S>(initial: S, mut step: impl FnMut(&S) -> Option<S>) -> Outcome<S>
+    where
+        S: Clone + Eq,
+    {
+        let Some(mut hare) = step(&initial) else {
+            return Outcome::Halted {
+                steps: 0,
+                state: initial,
+            };
+        };
+        let mut hare_steps = 1;
+        let mut tortoise = initial.clone();
+        let mut power = 1;
+        let mut len = 1;
+        while tortoise != hare {
+            if power == len {
+                tortoise = hare.clone();
+                power *= 2;
+                len = 0;
+            }
+            match step(&hare) {
+                Some(next) => hare = next,
+                None => {
+                    return Outcome::Halted {
+                        steps: hare_steps,
+                        state: hare,
+                    }
+                }
+            }
+            hare_steps += 1;
+            len += 1;
+        }
+
+        // NOTE: We know that every step from here on succeeds, since we're in a cycle.
+        let mut tortoise = initial;
+        let mut hare = tortoise.clone();
+        for _ in 0..len {
+            hare = step(&hare).unwrap();
+        }
+        let mut start = 0;
+        while tortoise != hare {
+            tortoise = step(&tortoise).unwrap();
+            hare = step(&hare).unwrap();
+            start += 1;
+        }
+        Outcome::Cycled(Cycle { start, len })
+    }
+
+    /// Computes the state after `num_steps` steps, skipping ahead once a cycle is found. Returns
+    /// `None` if `step` halts first.
+    pub fn state_after<S>(
+        initial: S,
+        step: impl FnMut(&S) -> Option<S>,
+        num_steps: usize,
+    ) -> Option<S>
+    where
+        S: Clone + Eq + Hash,
+    {
+        let mut history = History::new(initial);
+        match history.run(step, |steps| steps == num_steps) {
+            Outcome::Halted { steps, state } => (steps == num_steps).then_some(state),
+            Outcome::Cycled(Cycle { start, len }) => {
+                let idx = start + (num_steps - start) % len;
+                Some(history.states.swap_remove(idx))
+            }
+        }
+    }
+
+    struct History<S> {
+        states: Vec<S>,
+        indices: HashMap<S, usize>,
+    }
+
+    impl<S> History<S>
+    where
+        S: Clone + Eq + Hash,
+    {
+        fn new(initial: S) -> Self {
+            Self {
+                indices: HashMap::from([(initial.clone(), 0)]),
+                states: vec![initial],
+            }
+        }
+
+        /// Steps until halting, finding a cycle, or `stop` returns `true` for the number of
+        /// steps taken so far (which is reported as halting).
+        fn run(
+            &mut self,
+            mut step: impl FnMut(&S) -> Option<S>,
+            mut stop: impl FnMut(usize) -> bool,
+        ) -> Outcome<S> {
+            let Self { states, indices } = self;
+            loop {
+                let current = states.last().unwrap();
+                let steps = states.len() - 1;
+                let next = if stop(steps) { None } else { step(current) };
+                let Some(next) = next else {
+                    return Outcome::Halted {
+                        steps,
+                        state: current.clone(),
+                    };
+                };
+                if let Some(&start) = indices.get(&next) {
+                    return Outcome::Cycled(Cycle {
+                        start,
+                        len: states.len() - start,
+                    });
+                }
+                indices.insert(next.clone(), states.len());
+                states.push(next);
+            }
+        }
+    }
+}
+
+/// Dense collections keyed by [`space::d2::Coords`] within a fixed [`space::d2::Size`].
+pub mod collections {
+    use std::fmt::{self, Debug, Formatter};
+
+    use strum::IntoEnumIterator as _;
+
+    use crate::{
+        direction::Direction,
+        space::d2::{Coords, Size},
+    };
+
+    #[track_caller]
+    fn idx_of(size: Size, coords: Coords) -> usize {
+        size.row_major_idx(coords)
+            .unwrap_or_else(|| panic!("{coords:?} is out of bounds of {size:?}"))
+    }
+
+    /// A set of [`Coords`], stored as one bit per cell.
+    #[derive(Clone, Eq, PartialEq)]
+    pub struct CoordSet {
+        size: Size,
+        words: Vec<u64>,
+        len: usize,
+    }
+
+    impl CoordSet {
+        pub fn new(size: Size) -> Self {
+            Self {
+                size,
+                words: vec![0; size.area().div_ceil(64)],
+                len: 0,
+            }
+        }
+
+        pub fn size(&self) -> Size {
+            self.size
+        }
+
+        /// Returns whether `coords` was newly inserted. Panics if `coords` is out of bounds.
+        #[track_caller]
+        pub fn insert(&mut self, coords: Coords) -> bool {
+            let Self { size, words, len } = self;
+            let idx = idx_of(*size, coords);
+            let (word, bit) = (&mut words[idx / 64], 1 << (idx % 64));
+            let newly_inserted = *word & bit == 0;
+            *word |= bit;
+            *len += usize::from(newly_inserted);
+            newly_inserted
+        }
+
+        /// Returns whether `coords` was present.
+        pub fn remove(&mut self, coords: Coords) -> bool {
+            let Self { size, words, len } = self;
+            let Some(idx) = size.row_major_idx(coords) else {
+                return false;
+            };
+            let (word, bit) = (&mut words[idx / 64], 1 << (idx % 64));
+            let was_present = *word & bit != 0;
+            *word &= !bit;
+            *len -= usize::from(was_present);
+            was_present
+        }
+
+        pub fn contains(&self, coords: Coords) -> bool {
+            let Self {
+                size,
+                words,
+                len: _,
+            } = self;
+            size.row_major_idx(coords)
+                .is_some_and(|idx| words[idx / 64] & (1 << (idx % 64)) != 0)
+        }
+
+        pub fn len(&self) -> usize {
+            self.len
+        }
+
+        pub fn is_empty(&self) -> bool {
+            self.len == 0
+        }
+
+        pub fn clear(&mut self) {
+            let Self {
+                size: _,
+                words,
+                len,
+            } = self;
+            words.fill(0);
+            *len = 0;
+        }
+
+        /// Iterates in row-major order.
+        pub fn iter(&self) -> impl Iterator<Item = Coords> + Clone + '_ {
+            let Self {
+                size,
+                words,
+                len: _,
+            } = self;
+            words.iter().enumerate().flat_map(move |(word_idx, &word)| {
+                let mut remaining = word;
+                std::iter::from_fn(move || {
+                    (remaining != 0).then(|| {
+                        let bit_idx = remaining.trailing_zeros() as usize;
+                        remaining &= remaining - 1;
+                        size.coords_at_row_major_idx(word_idx * 64 + bit_idx)
+                            .unwrap()
+                    })
+                })
+            })
+        }
+    }
+
+    impl Debug for CoordSet {
+        fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+            f.debug_set().entries(self.iter()).finish()
+        }
+    }
+
+    /// A map from [`Coords`] to `T`, stored as a flat buffer with a slot for every cell.
+    #[derive(Clone, Debug, Eq, PartialEq)]
+    pub struct CoordMap<T> {
+        size: Size,
+        cells: Vec<Option<T>>,
+        len: usize,
+    }
+
+    impl<T> CoordMap<T> {
+        pub fn new(size: Size) -> Self {
+            Self {
+                size,
+                cells: std::iter::repeat_with(|| None).take(size.area()).collect(),
+                len: 0,
+            }
+        }
+
+        pub fn size(&self) -> Size {
+            self.size
+        }
+
+        /// Panics if `coords` is out of bounds.
+        #[track_caller]
+        pub fn insert(&mut self, coords: Coords, value: T) -> Option<T> {
+            let Self { size, cells, len } = self;
+            let old = cells[idx_of(*size, coords)].replace(value);
+            *len += usize::from(old.is_none());
+            old
+        }
+
+        pub fn remove(&mut self, coords: Coords) -> Option<T> {
+            let Self { size, cells, len } = self;
+            let old = cells[size.row_major_idx(coords)?].take();
+            *len -= usize::from(old.is_some());
+            old
+        }
+
+        pub fn get(&self, coords: Coords) -> Option<&T> {
+            let Self {
+                size,
+                cells,
+                len: _,
+            } = self;
+            cells[size.row_major_idx(coords)?].as_ref()
+        }
+
+        pub fn get_mut(&mut self, coords: Coords) -> Option<&mut T> {
+            let Self {
+                size,
+                cells,
+                len: _,
+            } = self;
+            cells[size.row_major_idx(coords)?].as_mut()
+        }
+
+        /// Panics if `coords` is out of bounds.
+        #[track_caller]
+        pub fn get_or_insert_with(
+            &mut self,
+            coords: Coords,
+            default: impl FnOnce() -> T,
+        ) -> &mut T {
+            let Self { size, cells, len } = self;
+            let cell = &mut cells[idx_of(*size, coords)];
+            if cell.is_none() {
+                *len += 1;
+            }
+            cell.get_or_insert_with(default)
+        }
+
+        pub fn contains_key(&self, coords: Coords) -> bool {
+            self.get(coords).is_some()
+        }
+
+        pub fn len(&self) -> usize {
+            self.len
+        }
+
+        pub fn is_empty(&self) -> bool {
+            self.len == 0
+        }
+
+        /// Iterates in row-major order.
+        pub fn iter(&self) -> impl Iterator<Item = (Coords, &T)> + Clone {
+            let Self {
+                size,
+                cells,
+                len: _,
+            } = self;
+            size.coords()
+                .zip(cells.iter())
+                .filter_map(|(coords, value)| Some((coords, value.as_ref()?)))
+        }
+    }
+
+    /// A set of `(Coords, Direction)` pairs, stored as four bits per cell; e.g., for tracking
+    /// which ways a cell has been traversed.
+    #[derive(Clone, Debug, Eq, PartialEq)]
+    pub struct DirectionalCoordSet {
+        size: Size,
+        cells: Vec<u8>,
+        len: usize,
+    }
+
+    impl DirectionalCoordSet {
+        pub fn new(size: Size) -> Self {
+            Self {
+                size,
+                cells: vec![0; size.area()],
+                len: 0,
+            }
+        }
+
+        fn bit(direction: Direction) -> u8 {
+            match direction {
+                Direction::North => 0b0001,
+                Direction::East => 0b0010,
+                Direction::South => 0b0100,
+                Direction::West => 0b1000,
+            }
+        }
+
+        pub fn size(&self) -> Size {
+            self.size
+        }
+
+        /// Returns whether the pair was newly inserted. Panics if `coords` is out of bounds.
+        #[track_caller]
+        pub fn insert(&mut self, coords: Coords, direction: Direction) -> bool {
+            let Self { size, cells, len } = self;
+            let (cell, bit) = (&mut cells[idx_of(*size, coords)], Self::bit(direction));
+            let newly_inserted = *cell & bit == 0;
+            *cell |= bit;
+            *len += usize::from(newly_inserted);
+            newly_inserted
+        }
+
+        pub fn contains(&self, coords: Coords, direction: Direction) -> bool {
+            let Self {
+                size,
+                cells,
+                len: _,
+            } = self;
+            size.row_major_idx(coords)
+                .is_some_and(|idx| cells[idx] & Self::bit(direction) != 0)
+        }
+
+        /// Whether `coords` is present with any direction.
+        pub fn contains_coords(&self, coords: Coords) -> bool {
+            let Self {
+                size,
+                cells,
+                len: _,
+            } = self;
+            size.row_major_idx(coords)
+                .is_some_and(|idx| cells[idx] != 0)
+        }
+
+        pub fn directions(&self, coords: Coords) -> impl Iterator<Item = Direction> + '_ {
+            Direction::iter().filter(move |direction| self.contains(coords, *direction))
+        }
+
+        /// The number of `(Coords, Direction)` pairs.
+        pub fn len(&self) -> usize {
+            self.len
+        }
+
+        pub fn is_empty(&self) -> bool {
+            self.len == 0
+        }
+
+        pub fn clear(&mut self) {
+            let Self {
+                size: _,
+                cells,
+                len,
+            } = self;
+            cells.fill(0);
+            *len = 0;
+        }
+
+        /// Iterates in row-major order over [`Coords`] present with any direction.
+        pub fn coords(&self) -> impl Iterator<Item = Coords> + Clone + '_ {
+            let Self {
+                size,
+                cells,
+                len: _,
+            } = self;
+            size.coords()
+                .zip(cells.iter())
+                .filter_map(|(coords, cell)| (*cell != 0).then_some(coords))
+        }
+    }
+}
+
+/// ASCII rendering of grids with overlays, e.g., for comparing against puzzle text or
+/// snapshotting with `insta`.
+pub mod render {
+    use std::fmt::{self, Display, Formatter};
+
+    use crate::{
+        collections::CoordSet,
+        direction::Direction,
+        grid::Grid,
+        space::d2::{Coords, Size},
+    };
+
+    /// A grid of characters to draw on. Later drawing replaces earlier drawing.
+    #[derive(Clone, Debug, Eq, PartialEq)]
+    pub struct Canvas {
+        cells: Grid<char>,
+    }
+
+    impl Canvas {
+        pub fn new(size: Size, background: char) -> Self {
+            Self {
+                cells: Grid::from_fn(size, |_coords| background),
+            }
+        }
+
+        pub fn from_grid<T>(grid: &Grid<T>, tile: impl FnMut(&T) -> char) -> Self {
+            Self {
+                cells: grid.map(tile),
+            }
+        }
+
+        pub fn size(&self) -> Size {
+            self.cells.size()
+        }
+
+        pub fn get(&self, coords: Coords) -> Option<char> {
+            self.cells.get(coords).copied()
+        }
+
+        /// Draws `c` at `coords`, ignoring out-of-bounds `coords`.
+        pub fn draw(&mut self, coords: Coords, c: char) {
+            if let Some(cell) = self.cells.get_mut(coords) {
+                *cell = c;
+            }
+        }
+
+        pub fn draw_all(&mut self, coords: impl IntoIterator<Item = Coords>, c: char) {
+            for coords in coords {
+                self.draw(coords, c);
+            }
+        }
+
+        pub fn draw_set(&mut self, set: &CoordSet, c: char) {
+            self.draw_all(set.iter(), c);
+        }
+
+        /// Draws each step of `path` as an arrow (i.e., `^>v<`) in its direction.
+        pub fn draw_path(&mut self, path: impl IntoIterator<Item = (Coords, Direction)>) {
+            for (coords, direction) in path {
+                self.draw(coords, direction.to_arrow());
+            }
+        }
+
+        /// Calls `overlay` with every cell's coordinates and current character, and draws
+        /// whatever it returns.
+        pub fn draw_with(&mut self, mut overlay: impl FnMut(Coords, char) -> Option<char>) {
+            for coords in self.cells.size().coords() {
+                let cell = &mut self.cells[coords];
+                if let Some(c) = overlay(coords, *cell) {
+                    *cell = c;
+                }
+            }
+        }
+    }
+
+    /// Renders one line per row, each terminated by a newline.
+    impl Display for Canvas {
+        fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+            for row in self.cells.rows() {
+                for c in row {
+                    write!(f, "{c}")?;
+                }
+                writeln!(f)?;
+            }
+            Ok(())
+        }
+    }
+}
+
+pub mod ordering {
+    use std::{
+        cmp::{Ordering, Reverse},
+        collections::{BTreeSet, BinaryHeap, HashMap, HashSet},
+        fmt::{self, Debug, Display, Formatter},
+        hash::Hash,
+        str::FromStr,
+    };
+
+    /// A set of `before|after` rules, each of which says that one item must come before another.
+    #[derive(Clone, Debug)]
+    pub struct PrecedenceGraph<T> {
+        successors: HashMap<T, HashSet<T>>,
+    }
+
+    impl<T> Default for PrecedenceGraph<T> {
+        fn default() -> Self {
+            Self {
+                successors: HashMap::new(),
+            }
+        }
+    }
+
+    impl<T> PrecedenceGraph<T>
+    where
+        T: Copy + Eq + Hash + Ord,
+    {
+        pub fn new() -> Self {
+            Self::default()
+        }
+
+        /// Parses one `before|after` rule per line.
+        pub fn parse_rules<'a>(
+            lines: impl IntoIterator<Item = &'a str>,
+        ) -> Result<Self, RuleParseError>
+        where
+            T: FromStr,
+        {
+            let mut graph = Self::new();
+            for (line_idx, line) in lines.into_iter().enumerate() {
+                let rule = line.split_once('|').and_then(|(before, after)| {
+                    Some((before.trim().parse().ok()?, after.trim().parse().ok()?))
+                });
+                let Some((before, after)) = rule else {
+                    return Err(RuleParseError {
+                        line_num: line_idx + 1,
+                        line: line.to_owned(),
+                    });
+                };
+                graph.add_rule(before, after);
+            }
+            Ok(graph)
+        }
+
+        pub fn add_rule(&mut self, before: T, after: T) {
+            let Self { successors } = self;
+            successors.entry(before).or_default().insert(after);
+        }
+
+        /// Whether there's a rule that says that `before` must come before `after`. This does
+        /// _not_ follow chains of rules.
+        pub fn must_precede(&self, before: T, after: T) -> bool {
+            let Self { successors } = self;
+            successors
+                .get(&before)
+                .is_some_and(|afters| afters.contains(&after))
+        }
+
+        /// Whether no rule is broken by `seq`'s order.
+        pub fn is_consistent(&self, seq: &[T]) -> bool {
+            let Self { successors } = self;
+            let idx_by_item = seq
+                .iter()
+                .enumerate()
+                .map(|(idx, item)| (item, idx))
+                .collect::<HashMap<_, _>>();
+            seq.iter().enumerate().all(|(idx, item)| {
+                successors.get(item).is_none_or(|afters| {
+                    afters
+                        .iter()
+                        .filter_map(|after| idx_by_item.get(after))
+                        .all(|&after_idx| after_idx > idx)
+                })
+            })
+        }
+
+        /// Orders `subset` so that it breaks no rule between its items, using Kahn's algorithm.
+        /// Rules that involve items outside of `subset` are ignored.
+        ///
+        /// When more than one item may come next, the smallest is picked, so the result is the
+        /// same no matter what order `subset` is given in.
+        ///
+        /// `subset` is treated as a set, so duplicate items are removed, and the result may be
+        /// shorter than `subset`.
+        pub fn topological_order(
+            &self,
+            subset: impl IntoIterator<Item = T>,
+        ) -> Result<Vec<T>, PrecedenceCycle<T>> {
+            let Self { successors } = self;
+            let subset = subset.into_iter().collect::<BTreeSet<_>>();
+
+            let successors_in_subset = |item: &T| {
+                successors
+                    .get(item)
+                    .into_iter()
+                    .flatten()
+                    .filter(|after| subset.contains(after))
+            };
+
+            let mut num_predecessors = subset
+                .iter()
+                .map(|&item| (item, 0usize))
+                .collect::<HashMap<_, _>>();
+            for item in &subset {
+                for after in successors_in_subset(item) {
+                    *num_predecessors.get_mut(after).unwrap() += 1;
+                }
+            }
+
+            let mut ready = num_predecessors
+                .iter()
+                .filter(|&(_item, &count)| count == 0)
+                .map(|(&item, _count)| Reverse(item))
+                .collect::<BinaryHeap<_>>();
+            let mut order = Vec::with_capacity(subset.len());
+            while let Some(Reverse(item)) = ready.pop() {
+                order.push(item);
+                for &after in successors_in_subset(&item) {
+                    let count = num_predecessors.get_mut(&after).unwrap();
+                    *count -= 1;
+                    if *count == 0 {
+                        ready.push(Reverse(after));
+                    }
+                }
+            }
+
+            if order.len() == subset.len() {
+                return Ok(order);
+            }
+
+            // NOTE: Every item that didn't make it into `order` still has a predecessor that
+            // didn't either, so walking backwards from any of them must eventually loop.
+            let remaining = subset
+                .iter()
+                .copied()
+                .filter(|item| num_predecessors[item] != 0)
+                .collect::<BTreeSet<_>>();
+            let predecessor_of = |item: T| {
+                remaining
+                    .iter()
+                    .copied()
+                    .find(|&before| self.must_precede(before, item))
+                    .unwrap()
+            };
+            let mut walk = vec![*remaining.first().unwrap()];
+            let cycle_start = loop {
+                let before = predecessor_of(*walk.last().unwrap());
+                if let Some(idx) = walk.iter().position(|&item| item == before) {
+                    break idx;
+                }
+                walk.push(before);
+            };
+            let mut items = walk.split_off(cycle_start);
+            items.reverse();
+            Err(PrecedenceCycle { items })
+        }
+    }
+
+    impl<T> FromIterator<(T, T)> for PrecedenceGraph<T>
+    where
+        T: Copy + Eq + Hash + Ord,
+    {
+        fn from_iter<I: IntoIterator<Item = (T, T)>>(rules: I) -> Self {
+            let mut graph = Self::new();
+            for (before, after) in rules {
+                graph.add_rule(before, after);
+            }
+            graph
+        }
+    }
+
+    /// Rules that contradict each other: each item must come before the next, and the last
+    /// must come before the first.
+    #[derive(Clone, Debug, Eq, PartialEq)]
+    pub struct PrecedenceCycle<T> {
+        pub items: Vec<T>,
+    }
+
+    impl<T> Display for PrecedenceCycle<T>
+    where
+        T: Display,
+    {
+        fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+            let Self { items } = self;
+            f.write_str("contradictory rules: ")?;
+            for (idx, before) in items.iter().enumerate() {
+                let after = &items[(idx + 1) % items.len()];
+                if idx != 0 {
+                    f.write_str(", ")?;
+                }
+                write!(f, "{before}|{after}")?;
+            }
+            Ok(())
+        }
+    }
+
+    impl<T> std::error::Error for PrecedenceCycle<T> where T: Debug + Display {}
+
+    #[derive(Clone, Debug, Eq, PartialEq)]
+    pub struct RuleParseError {
+        /// 1-based.
+        pub line_num: usize,
+        pub line: String,
+    }
+
+    impl Display for RuleParseError {
+        fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+            let Self { line_num, line } = self;
+            write!(
+                f,
+                "line {line_num} ({line:?}) is not a rule of the form `before|after`"
+            )
+        }
+    }
+
+    impl std::error::Error for RuleParseError {}
+
+    /// Day 5's page ordering rules, where each `before|after` rule is between two page numbers.
+    #[derive(Clone, Debug, Default)]
+    pub struct BeforeAfterRules {
+        inner: PrecedenceGraph<u8>,
+    }
+
+    impl BeforeAfterRules {
+        pub fn new(inner: PrecedenceGraph<u8>) -> Self {
+            Self { inner }
+        }
+
+        /// See [`PrecedenceGraph::parse_rules`].
+        pub fn parse<'a>(lines: impl IntoIterator<Item = &'a str>) -> Result<Self, RuleParseError> {
+            PrecedenceGraph::parse_rules(lines).map(Self::new)
+        }
+
+        pub fn precedence_graph(&self) -> &PrecedenceGraph<u8> {
+            &self.inner
+        }
+
+        /// Compares pages by the rule between them, if any. Pages with no rule between them
+        /// compare as equal, so this is only a total order for updates whose
+        /// [`Self::diagnose`]d [`UpdateDiagnostics::unrelated_pairs`] and
+        /// [`UpdateDiagnostics::non_transitive_triples`] are empty.
+        pub fn comparator(&self) -> impl Fn(&u8, &u8) -> Ordering + '_ {
+            let Self { inner } = self;
+            |&a, &b| {
+                if inner.must_precede(a, b) {
+                    Ordering::Less
+                } else if inner.must_precede(b, a) {
+                    Ordering::Greater
+                } else {
+                    Ordering::Equal
+                }
+            }
+        }
+
+        pub fn is_correctly_ordered(&self, update: &[u8]) -> bool {
+            let Self { inner } = self;
+            inner.is_consistent(update)
+        }
+
+        /// Sorts `update` into the [`PrecedenceGraph::topological_order`] of its pages, keeping
+        /// any duplicates next to each other. The result is only unique if the rules totally
+        /// order `update`; see [`Self::checked_sort`].
+        pub fn sort(&self, update: &mut [u8]) -> Result<(), PrecedenceCycle<u8>> {
+            let Self { inner } = self;
+            let order = inner.topological_order(update.iter().copied())?;
+            let mut rank_by_page = [0; 256];
+            for (rank, page) in order.into_iter().enumerate() {
+                rank_by_page[usize::from(page)] = rank;
+            }
+            update.sort_by_key(|&page| rank_by_page[usize::from(page)]);
+            Ok(())
+        }
+
+        /// Like [`Self::sort`], but first checks that the rules totally order `update`, which
+        /// takes cubic time.
+        ///
+        /// # Panics
+        ///
+        /// If [`Self::diagnose`] finds any problem other than an even length.
+        #[track_caller]
+        pub fn checked_sort(&self, update: &mut [u8]) {
+            let diagnostics = self.diagnose(update);
+            assert!(
+                diagnostics.unrelated_pairs.is_empty()
+                    && diagnostics.non_transitive_triples.is_empty(),
+                "rules don't totally order {update:?}: {diagnostics:?}"
+            );
+            // NOTE: Rules between every pair of pages with no 3-cycles among them can't have
+            // any longer cycles either.
+            self.sort(update).unwrap();
+        }
+
+        /// Finds everything about `update` that makes its middle page ambiguous.
+        pub fn diagnose(&self, update: &[u8]) -> UpdateDiagnostics {
+            let Self { inner } = self;
+
+            let mut unrelated_pairs = Vec::new();
+            let mut non_transitive_triples = Vec::new();
+            for (idx, &a) in update.iter().enumerate() {
+                for (other_idx, &b) in update.iter().enumerate().skip(idx + 1) {
+                    if !inner.must_precede(a, b) && !inner.must_precede(b, a) {
+                        unrelated_pairs.push((a, b));
+                    }
+                    // NOTE: Each 3-cycle is only reported once, starting from its earliest page.
+                    for &c in &update[other_idx + 1..] {
+                        let is_cycle = |[a, b, c]: [u8; 3]| {
+                            inner.must_precede(a, b)
+                                && inner.must_precede(b, c)
+                                && inner.must_precede(c, a)
+                        };
+                        if is_cycle([a, b, c]) {
+                            non_transitive_triples.push((a, b, c));
+                        } else if is_cycle([a, c, b]) {
+                            non_transitive_triples.push((a, c, b));
+                        }
+                    }
+                }
+            }
+
+            UpdateDiagnostics {
+                even_length: update.len() % 2 == 0,
+                unrelated_pairs,
+                non_transitive_triples,
+            }
+        }
+    }
+
+    /// Problems with an update that [`BeforeAfterRules`] can't resolve on its own.
+    #[derive(Clone, Debug, Default, Eq, PartialEq)]
+    pub struct UpdateDiagnostics {
+        /// There's no single middle page.
+        pub even_length: bool,
+        /// Pages with no rule between them, so their relative order is ambiguous.
+        pub unrelated_pairs: Vec<(u8, u8)>,
+        /// `(a, b, c)` where `a|b`, `b|c`, and `c|a`, so no order satisfies all three rules.
+        pub non_transitive_triples: Vec<(u8, u8, u8)>,
+    }
+
+    impl UpdateDiagnostics {
+        pub fn is_clean(&self) -> bool {
+            let Self {
+                even_length,
+                unrelated_pairs,
+                non_transitive_triples,
+            } = self;
+            !even_length && unrelated_pairs.is_empty() && non_transitive_triples.is_empty()
+        }
+    }
+}
+
+/// Synthesizing operators between terms so that they evaluate to a target value, evaluated
+/// left-to-right.
+pub mod calibration {
+    use std::fmt::{self, Display, Formatter};
+
+    /// A binary operator that can be run backwards.
+    pub trait Operation: strum::IntoEnumIterator + Copy {
+        /// How this operator is written between its operands, i.e., `+`.
+        fn symbol(&self) -> &'static str;
+
+        /// Returns `None` on overflow.
+        fn checked_execute(&self, lhs: u64, rhs: u64) -> Option<u64>;
+
+        #[track_caller]
+        fn execute(&self, lhs: u64, rhs: u64) -> u64 {
+            self.checked_execute(lhs, rhs).unwrap()
+        }
+
+        /// Finds the `lhs`es for which `self.execute(lhs, rhs) == result`.
+        fn unexecute(&self, result: u64, rhs: u64) -> Inverse;
+    }
+
+    /// See [`Operation::unexecute`].
+    #[derive(Clone, Copy, Debug, Eq, PartialEq)]
+    pub enum Inverse {
+        /// No `lhs` works.
+        None,
+        /// Exactly one `lhs` works.
+        Unique(u64),
+        /// Every `lhs` works; i.e., multiplying by 0.
+        Any,
+    }
+
+    /// Finds operators that, placed between `terms`, evaluate to `target`. Returns them in the
+    /// order that they appear, or `None` if there are none.
+    ///
+    /// This works from `target` backwards, undoing the last operator first, so that branches
+    /// that can't reach `target` (i.e., a division with a remainder) get pruned early. When more
+    /// than one assignment works, which one is returned is unspecified; see [`explain`] for the
+    /// first in left-to-right order.
+    pub fn solve<Op>(target: u64, terms: &[u64]) -> Option<Vec<Op>>
+    where
+        Op: Operation,
+    {
+        fn solve_rec<Op>(target: u64, terms: &[u64], ops: &mut Vec<Op>) -> bool
+        where
+            Op: Operation,
+        {
+            match terms {
+                [] => false,
+                [first] => *first == target,
+                [rest @ .., last] => Op::iter().any(|op| match op.unexecute(target, *last) {
+                    Inverse::None => false,
+                    Inverse::Unique(lhs) => {
+                        ops.push(op);
+                        let solved = solve_rec(lhs, rest, ops);
+                        if !solved {
+                            ops.pop();
+                        }
+                        solved
+                    }
+                    Inverse::Any => {
+                        // NOTE: Whatever `rest` evaluates to works, as long as evaluating it
+                        // doesn't overflow.
+                        let (&first, rest) = rest.split_first().unwrap();
+                        let rest_start = ops.len() + 1;
+                        ops.push(op);
+                        if !any_non_overflowing(first, rest, ops) {
+                            ops.pop();
+                            return false;
+                        }
+                        // NOTE: `ops` is built from right to left.
+                        ops[rest_start..].reverse();
+                        true
+                    }
+                }),
+            }
+        }
+
+        /// Finds operators to place between `acc` and `terms`, in order, without overflowing.
+        fn any_non_overflowing<Op>(acc: u64, terms: &[u64], ops: &mut Vec<Op>) -> bool
+        where
+            Op: Operation,
+        {
+            let Some((&term, rest)) = terms.split_first() else {
+                return true;
+            };
+            Op::iter().any(|op| {
+                let Some(acc) = op.checked_execute(acc, term) else {
+                    return false;
+                };
+                ops.push(op);
+                let found = any_non_overflowing(acc, rest, ops);
+                if !found {
+                    ops.pop();
+                }
+                found
+            })
+        }
+
+        let mut ops = Vec::with_capacity(terms.len().saturating_sub(1));
+        if !solve_rec(target, terms, &mut ops) {
+            return None;
+        }
+        ops.reverse();
+        Some(ops)
+    }
+
+    /// Runs `ops` between `terms`, left-to-right.
+    #[track_caller]
+    pub fn evaluate<Op>(terms: &[u64], ops: &[Op]) -> u64
+    where
+        Op: Operation,
+    {
+        let (first, rest) = terms.split_first().expect("no terms to evaluate");
+        assert_eq!(
+            rest.len(),
+            ops.len(),
+            "expected one fewer operator than terms"
+        );
+        rest.iter()
+            .zip(ops)
+            .fold(*first, |acc, (&term, op)| op.execute(acc, term))
+    }
+
+    /// The operators that [`explain`] found for an equation.
+    #[derive(Clone, Debug, Eq, PartialEq)]
+    pub struct Explanation<Op> {
+        pub target: u64,
+        pub terms: Vec<u64>,
+        pub ops: Vec<Op>,
+        /// What `ops` evaluate to, which is only different from `target` if no assignment of
+        /// operators hits it.
+        pub value: u64,
+    }
+
+    impl<Op> Explanation<Op> {
+        pub fn is_solved(&self) -> bool {
+            let Self { target, value, .. } = self;
+            target == value
+        }
+    }
+
+    /// Renders as, e.g., `3267 = 81 * 40 + 27`, or `83 != 17 * 5 (closest: 85)`.
+    impl<Op> Display for Explanation<Op>
+    where
+        Op: Operation,
+    {
+        fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+            let Self {
+                target,
+                terms,
+                ops,
+                value,
+            } = self;
+            let relation = if self.is_solved() { "=" } else { "!=" };
+            write!(f, "{target} {relation} {}", terms[0])?;
+            for (op, term) in ops.iter().zip(&terms[1..]) {
+                write!(f, " {} {term}", op.symbol())?;
+            }
+            if !self.is_solved() {
+                write!(f, " (closest: {value})")?;
+            }
+            Ok(())
+        }
+    }
+
+    /// Like [`solve`], but returns the first assignment of operators that evaluates to `target`
+    /// when ordered left-to-right by [`strum::IntoEnumIterator::iter`] (i.e., the first operator
+    /// varies slowest). When none does, finds the one that gets closest to it instead, which
+    /// tries every assignment, so it's only practical for a few terms.
+    #[track_caller]
+    pub fn explain<Op>(target: u64, terms: &[u64]) -> Explanation<Op>
+    where
+        Op: Operation,
+    {
+        fn closest_rec<Op>(
+            target: u64,
+            acc: u64,
+            terms: &[u64],
+            ops: &mut Vec<Op>,
+            best: &mut Option<(u64, Vec<Op>)>,
+        ) where
+            Op: Operation,
+        {
+            let Some((&term, rest)) = terms.split_first() else {
+                if best
+                    .as_ref()
+                    .is_none_or(|&(value, _)| acc.abs_diff(target) < value.abs_diff(target))
+                {
+                    *best = Some((acc, ops.clone()));
+                }
+                return;
+            };
+            for op in Op::iter() {
+                // NOTE: Anything that overflows is way farther from `target` than the
+                // alternatives.
+                if let Some(acc) = op.checked_execute(acc, term) {
+                    ops.push(op);
+                    closest_rec(target, acc, rest, ops, best);
+                    ops.pop();
+                }
+            }
+        }
+
+        let (&first, rest) = terms.split_first().expect("no terms to explain");
+        let (value, ops) = match solve::<Op>(target, terms) {
+            Some(_) => {
+                // NOTE: Fix each operator in turn to the first one that still leaves a solution
+                // for the rest.
+                let mut acc = first;
+                let mut ops = Vec::with_capacity(rest.len());
+                for (idx, &term) in rest.iter().enumerate() {
+                    let (op, next) = Op::iter()
+                        .find_map(|op| {
+                            let next = op.checked_execute(acc, term)?;
+                            let remaining = [&[next], &rest[idx + 1..]].concat();
+                            solve::<Op>(target, &remaining).map(|_| (op, next))
+                        })
+                        .unwrap();
+                    ops.push(op);
+                    acc = next;
+                }
+                (target, ops)
+            }
+            None => {
+                let mut best = None;
+                closest_rec(target, first, rest, &mut Vec::new(), &mut best);
+                best.expect("every assignment of operators overflowed")
+            }
+        };
+        Explanation {
+            target,
+            terms: terms.to_vec(),
+            ops,
+            value,
+        }
+    }
+}
+
+/// Disk maps, where files are laid out in spans of blocks with free space between them.
+pub mod disk {
+    use std::{cmp::Reverse, collections::BinaryHeap};
+
+    /// Some or all of the blocks of the file with ID `id`.
+    #[derive(Clone, Copy, Debug, Eq, PartialEq)]
+    pub struct FileSpan {
+        pub id: u32,
+        pub start: u32,
+        pub len: u32,
+    }
+
+    #[derive(Clone, Copy, Debug, Eq, PartialEq)]
+    pub struct FreeSpan {
+        pub start: u32,
+        pub len: u32,
+    }
+
+    #[derive(Clone, Debug, Eq, PartialEq)]
+    pub struct DiskMap {
+        /// Sorted by `start`.
+        files: Vec<FileSpan>,
+        /// Sorted by `start`.
+        free: Vec<FreeSpan>,
+    }
+
+    impl DiskMap {
+        /// Parses a dense disk map, where digits alternate between the length of a file and the
+        /// length of the free space after it. Files are numbered in the order they appear.
+        #[track_caller]
+        pub fn parse(input: &str) -> Self {
+            let input = input.trim();
+            assert!(
+                input.chars().all(|c| c.is_ascii_digit()),
+                "disk map must only contain digits"
+            );
+
+            let mut files = Vec::with_capacity(input.len() / 2 + 1);
+            let mut free = Vec::with_capacity(input.len() / 2);
+            let mut start = 0u32;
+            for (idx, len) in input.bytes().map(|b| u32::from(b - b'0')).enumerate() {
+                if idx % 2 == 0 {
+                    let id = u32::try_from(idx / 2).unwrap();
+                    files.push(FileSpan { id, start, len });
+                } else if len != 0 {
+                    free.push(FreeSpan { start, len });
+                }
+                start = start.checked_add(len).unwrap();
+            }
+            Self { files, free }
+        }
+
+        /// Builds a disk map out of `files`, with free space in whatever gaps they leave.
+        #[track_caller]
+        pub fn from_files(mut files: Vec<FileSpan>) -> Self {
+            files.sort_by_key(|file| file.start);
+            let mut free = Vec::new();
+            let mut end = 0u32;
+            for file in &files {
+                assert!(
+                    file.start >= end,
+                    "{file:?} overlaps with the file before it"
+                );
+                if file.start != end {
+                    free.push(FreeSpan {
+                        start: end,
+                        len: file.start - end,
+                    });
+                }
+                end = file.start.checked_add(file.len).unwrap();
+            }
+            Self { files, free }
+        }
+
+        pub fn files(&self) -> &[FileSpan] {
+            &self.files
+        }
+
+        pub fn free(&self) -> &[FreeSpan] {
+            &self.free
+        }
+
+        /// Yields the ID of the file in each block, up to the end of the last file.
+        pub fn blocks(&self) -> impl Iterator<Item = Option<u32>> + '_ {
+            let Self { files, free: _ } = self;
+            let mut end = 0u32;
+            files.iter().flat_map(move |file| {
+                let gap = file.start - end;
+                end = file.start + file.len;
+                std::iter::repeat_n(None, gap.try_into().unwrap()).chain(std::iter::repeat_n(
+                    Some(file.id),
+                    file.len.try_into().unwrap(),
+                ))
+            })
+        }
+
+        /// Moves file blocks one at a time from the end of the disk into the leftmost free block,
+        /// until there are no gaps between files. Files may get split up along the way.
+        pub fn compact_blocks(&self) -> Self {
+            let Self { files, free } = self;
+
+            let mut files = files.clone();
+            let mut moved = Vec::new();
+            'free: for span in free {
+                let mut filled = 0;
+                while filled < span.len {
+                    let Some(last) = files.last_mut() else {
+                        break 'free;
+                    };
+                    if last.start <= span.start {
+                        break 'free;
+                    }
+                    if last.len == 0 {
+                        files.pop();
+                        continue;
+                    }
+                    let len = last.len.min(span.len - filled);
+                    moved.push(FileSpan {
+                        id: last.id,
+                        start: span.start + filled,
+                        len,
+                    });
+                    last.len -= len;
+                    if last.len == 0 {
+                        files.pop();
+                    }
+                    filled += len;
+                }
+            }
+
+            files.extend(moved);
+            Self::from_files(files)
+        }
+
+        /// Moves each whole file, in decreasing order of ID, into the leftmost free span that can
+        /// fit it, if that's to the left of it. Each file is tried only once.
+        ///
+        /// Free spans are indexed with a min-heap of starts per length, so finding the leftmost
+        /// fitting span is a matter of peeking at a few heaps.
+        pub fn compact_files(&self) -> Self {
+            let Self { files, free } = self;
+
+            let max_len = free.iter().map(|span| span.len).max().unwrap_or(0);
+            let mut free_starts_by_len =
+                vec![BinaryHeap::<Reverse<u32>>::new(); usize::try_from(max_len).unwrap() + 1];
+            for span in free {
+                free_starts_by_len[usize::try_from(span.len).unwrap()].push(Reverse(span.start));
+            }
+
+            let mut files = files.clone();
+            files.sort_by_key(|file| Reverse(file.id));
+            for file in &mut files {
+                let leftmost_fit = free_starts_by_len
+                    .iter()
+                    .enumerate()
+                    .skip(file.len.try_into().unwrap())
+                    .filter_map(|(len, starts)| Some((starts.peek()?.0, len)))
+                    .min();
+                let Some((start, len)) = leftmost_fit else {
+                    continue;
+                };
+                if start >= file.start {
+                    continue;
+                }
+
+                free_starts_by_len[len].pop();
+                file.start = start;
+                // NOTE: The space that `file` leaves behind is to the right of every file that's
+                // left to move, so it can never be used.
+                let remaining_len = len - usize::try_from(file.len).unwrap();
+                if remaining_len != 0 {
+                    free_starts_by_len[remaining_len].push(Reverse(start + file.len));
+                }
+            }
+
+            Self::from_files(files)
+        }
 
-            let coords = crate::space::d2::apply_rel_offset(bounds, origin, offset)?;
-            let crate::space::d2::Coords { row, col } = coords;
-            let [row, col] = [row, col].map(|coord| coord.into_inner());
-            Some((row, col))
+        /// The sum of each block's position multiplied by the ID of the file in it.
+        pub fn checksum(&self) -> u64 {
+            let Self { files, free: _ } = self;
+            files
+                .iter()
+                .map(|&FileSpan { id, start, len }| {
+                    let (start, len) = (u64::from(start), u64::from(len));
+                    // NOTE: `start + (start + 1) + … + (start + len - 1)`
+                    let position_sum = start * len + len * len.saturating_sub(1) / 2;
+                    u64::from(id).checked_mul(position_sum).unwrap()
+                })
+                .fold(0u64, |acc, x| acc.checked_add(x).unwrap())
         }
     }
 }
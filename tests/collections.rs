@@ -0,0 +1,139 @@
+use advent_of_code_2024::{
+    collections::{CoordMap, CoordSet, DirectionalCoordSet},
+    direction::Direction,
+    space::d2::{Coords, Size},
+};
+
+fn coords(row: usize, col: usize) -> Coords {
+    Coords::from_row_major((row, col))
+}
+
+#[test]
+fn coord_set() {
+    let mut set = CoordSet::new(Size::from_row_major((3, 30)));
+    assert!(set.is_empty());
+    assert!(set.insert(coords(2, 29)));
+    assert!(set.insert(coords(0, 3)));
+    assert!(!set.insert(coords(0, 3)));
+    // Crosses a word boundary
+    assert!(set.insert(coords(2, 5)));
+    assert_eq!(set.len(), 3);
+    assert!(set.contains(coords(2, 5)));
+    assert!(!set.contains(coords(2, 6)));
+    assert!(!set.contains(coords(3, 0)));
+    assert_eq!(
+        set.iter().collect::<Vec<_>>(),
+        [coords(0, 3), coords(2, 5), coords(2, 29)]
+    );
+
+    assert!(set.remove(coords(0, 3)));
+    assert!(!set.remove(coords(0, 3)));
+    assert!(!set.remove(coords(5, 5)));
+    assert_eq!(set.len(), 2);
+    set.clear();
+    assert!(set.is_empty());
+    assert_eq!(set.iter().count(), 0);
+}
+
+#[test]
+#[should_panic(expected = "out of bounds")]
+fn coord_set_out_of_bounds() {
+    CoordSet::new(Size::from_row_major((2, 2))).insert(coords(0, 2));
+}
+
+#[test]
+fn coord_map() {
+    let mut map = CoordMap::new(Size::from_row_major((2, 3)));
+    assert!(map.is_empty());
+    assert_eq!(map.insert(coords(1, 2), 'a'), None);
+    assert_eq!(map.insert(coords(0, 1), 'b'), None);
+    assert_eq!(map.insert(coords(1, 2), 'c'), Some('a'));
+    assert_eq!(map.len(), 2);
+    assert_eq!(map.get(coords(1, 2)), Some(&'c'));
+    assert_eq!(map.get(coords(0, 0)), None);
+    assert_eq!(map.get(coords(2, 0)), None);
+    assert!(map.contains_key(coords(0, 1)));
+    assert!(!map.contains_key(coords(0, 2)));
+
+    *map.get_mut(coords(0, 1)).unwrap() = 'd';
+    assert_eq!(map.get_mut(coords(1, 1)), None);
+    assert_eq!(map.get_mut(coords(9, 9)), None);
+
+    // Only inserting a new value counts towards the length.
+    assert_eq!(*map.get_or_insert_with(coords(0, 1), || 'x'), 'd');
+    assert_eq!(map.len(), 2);
+    *map.get_or_insert_with(coords(0, 0), || 'e') = 'f';
+    assert_eq!(map.len(), 3);
+    assert_eq!(
+        map.iter().collect::<Vec<_>>(),
+        [
+            (coords(0, 0), &'f'),
+            (coords(0, 1), &'d'),
+            (coords(1, 2), &'c')
+        ]
+    );
+
+    assert_eq!(map.remove(coords(0, 1)), Some('d'));
+    assert_eq!(map.remove(coords(0, 1)), None);
+    assert_eq!(map.remove(coords(5, 0)), None);
+    assert_eq!(map.len(), 2);
+    assert_eq!(map.remove(coords(0, 0)), Some('f'));
+    assert_eq!(map.remove(coords(1, 2)), Some('c'));
+    assert!(map.is_empty());
+    assert_eq!(map.iter().count(), 0);
+}
+
+#[test]
+#[should_panic(expected = "out of bounds")]
+fn coord_map_insert_out_of_bounds() {
+    CoordMap::new(Size::from_row_major((2, 3))).insert(coords(2, 0), ());
+}
+
+#[test]
+#[should_panic(expected = "out of bounds")]
+fn coord_map_get_or_insert_with_out_of_bounds() {
+    CoordMap::new(Size::from_row_major((2, 3))).get_or_insert_with(coords(0, 3), || ());
+}
+
+#[test]
+fn directional_coord_set() {
+    use Direction::*;
+
+    let mut set = DirectionalCoordSet::new(Size::from_row_major((2, 2)));
+    assert!(set.is_empty());
+    assert!(set.insert(coords(1, 0), East));
+    assert!(set.insert(coords(1, 0), North));
+    assert!(!set.insert(coords(1, 0), East));
+    assert!(set.insert(coords(0, 1), West));
+    // Each direction counts separately.
+    assert_eq!(set.len(), 3);
+
+    assert!(set.contains(coords(1, 0), East));
+    assert!(!set.contains(coords(1, 0), South));
+    assert!(!set.contains(coords(0, 0), East));
+    assert!(!set.contains(coords(2, 0), East));
+    assert!(set.contains_coords(coords(0, 1)));
+    assert!(!set.contains_coords(coords(1, 1)));
+    assert!(!set.contains_coords(coords(0, 2)));
+
+    assert_eq!(
+        set.directions(coords(1, 0)).collect::<Vec<_>>(),
+        [North, East]
+    );
+    assert_eq!(set.directions(coords(1, 1)).count(), 0);
+    assert_eq!(
+        set.coords().collect::<Vec<_>>(),
+        [coords(0, 1), coords(1, 0)]
+    );
+
+    set.clear();
+    assert!(set.is_empty());
+    assert_eq!(set.coords().count(), 0);
+    assert!(!set.contains(coords(1, 0), East));
+}
+
+#[test]
+#[should_panic(expected = "out of bounds")]
+fn directional_coord_set_out_of_bounds() {
+    DirectionalCoordSet::new(Size::from_row_major((2, 2))).insert(coords(0, 2), Direction::North);
+}
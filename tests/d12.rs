@@ -0,0 +1,170 @@
+use std::collections::HashSet;
+
+use advent_of_code_2024::{
+    grid::Grid,
+    regions::{flood_fill, Regions},
+    space::d2::Coords,
+};
+
+const EXAMPLE_1: &str = "\
+AAAA
+BBCD
+BBCC
+EEEC
+";
+
+const EXAMPLE_2: &str = "\
+OOOOO
+OXOXO
+OOOOO
+OXOXO
+OOOOO
+";
+
+const EXAMPLE_3: &str = "\
+RRRRIICCFF
+RRRRIICCCF
+VVRRRCCFFF
+VVRCCCJFFF
+VVVVCJJCFE
+VVIVCCJJEE
+VVIIICJJEE
+MIIIIIJJEE
+MIIISIJEEE
+MMMISSJEEE
+";
+
+fn find_regions(input: &str) -> (Grid<u8>, Regions) {
+    let grid = Grid::from_ascii_lines(input.lines(), |_coords, byte| byte);
+    let regions = Regions::find(&grid, |a, b| a == b);
+    (grid, regions)
+}
+
+#[test]
+fn regions() {
+    let (grid, regions) = find_regions(EXAMPLE_1);
+    let summary = regions
+        .regions()
+        .iter()
+        .map(|region| {
+            (
+                char::from(grid[region.representative]),
+                region.area,
+                region.perimeter,
+                region.sides,
+            )
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        summary,
+        [
+            ('A', 4, 10, 4),
+            ('B', 4, 8, 4),
+            ('C', 4, 10, 8),
+            ('D', 1, 4, 4),
+            ('E', 3, 8, 4),
+        ]
+    );
+    for (coords, &label) in regions.labels().iter() {
+        assert_eq!(regions.region_at(coords).unwrap().label, label);
+    }
+
+    // The `X`s are all separate regions, and the `O` region has holes in it.
+    let (grid, regions) = find_regions(EXAMPLE_2);
+    assert_eq!(regions.regions().len(), 5);
+    let o_region = regions
+        .region_at(grid.size().coords().next().unwrap())
+        .unwrap();
+    assert_eq!(
+        (o_region.area, o_region.perimeter, o_region.sides),
+        (21, 36, 20)
+    );
+
+    let (_grid, regions) = find_regions(EXAMPLE_3);
+    assert_eq!(regions.regions().len(), 11);
+}
+
+#[test]
+fn flood_fill_matches_regions() {
+    let (grid, regions) = find_regions(EXAMPLE_2);
+
+    let corner = Coords::from_row_major((0, 0));
+    let o_cells = flood_fill(&grid, corner, |a, b| a == b);
+    assert_eq!(o_cells[0], corner);
+    assert_eq!(o_cells.len(), 21);
+    let o_cells = o_cells.into_iter().collect::<HashSet<_>>();
+    assert_eq!(o_cells.len(), 21, "cells shouldn't be found twice");
+    let o_label = regions.labels()[corner];
+    assert_eq!(
+        o_cells,
+        regions
+            .labels()
+            .iter()
+            .filter(|&(_coords, &label)| label == o_label)
+            .map(|(coords, _label)| coords)
+            .collect()
+    );
+
+    let x = Coords::from_row_major((1, 1));
+    assert_eq!(flood_fill(&grid, x, |a, b| a == b), [x]);
+
+    // Every cell is reachable when everything is considered the same region.
+    assert_eq!(flood_fill(&grid, x, |_a, _b| true).len(), 25);
+}
+
+fn total_fencing_price_p1(input: &str) -> usize {
+    let (_grid, regions) = find_regions(input);
+    regions
+        .regions()
+        .iter()
+        .map(|region| region.area * region.perimeter)
+        .sum()
+}
+
+#[test]
+fn p1_example() {
+    assert_eq!(total_fencing_price_p1(EXAMPLE_1), 140);
+    assert_eq!(total_fencing_price_p1(EXAMPLE_2), 772);
+    assert_eq!(total_fencing_price_p1(EXAMPLE_3), 1930);
+}
+
+fn total_fencing_price_p2(input: &str) -> usize {
+    let (_grid, regions) = find_regions(input);
+    regions
+        .regions()
+        .iter()
+        .map(|region| region.area * region.sides)
+        .sum()
+}
+
+#[test]
+fn p2_example() {
+    assert_eq!(total_fencing_price_p2(EXAMPLE_1), 80);
+    assert_eq!(total_fencing_price_p2(EXAMPLE_2), 436);
+    assert_eq!(
+        total_fencing_price_p2(
+            "\
+EEEEE
+EXXXX
+EEEEE
+EXXXX
+EEEEE
+"
+        ),
+        236
+    );
+    assert_eq!(
+        total_fencing_price_p2(
+            "\
+AAAAAA
+AAABBA
+AAABBA
+ABBAAA
+ABBAAA
+AAAAAA
+"
+        ),
+        368
+    );
+    assert_eq!(total_fencing_price_p2(EXAMPLE_3), 1206);
+}
@@ -0,0 +1,170 @@
+use std::collections::HashSet;
+
+use advent_of_code_2024::{
+    direction::Direction,
+    grid::{markers::MarkerSpec, Grid},
+    search::{astar, bfs, dijkstra, grid_successors, oriented_grid_successors, Predecessors},
+    space::d2::Coords,
+};
+
+const EXAMPLE_1: &str = "\
+###############
+#.......#....E#
+#.#.###.#.###.#
+#.....#.#...#.#
+#.###.#####.#.#
+#.#.#.......#.#
+#.#.#####.###.#
+#...........#.#
+###.#.#####.#.#
+#...#.....#.#.#
+#.#.#.###.#.#.#
+#.....#...#.#.#
+#.###.#.#.#.#.#
+#S..#.....#...#
+###############
+";
+
+const EXAMPLE_2: &str = "\
+#################
+#...#...#...#..E#
+#.#.#.#.#.#.#.#.#
+#.#.#.#...#...#.#
+#.#.#.#.###.#.#.#
+#...#.#.#.....#.#
+#.#.#.#.#.#####.#
+#.#...#.#.#.....#
+#.#.#####.#.###.#
+#.#.#.......#...#
+#.#.###.#####.###
+#.#.#...#.....#.#
+#.#.#.#####.###.#
+#.#.#.........#.#
+#.#.#.#########.#
+#S#.............#
+#################
+";
+
+struct Maze {
+    walls: Grid<bool>,
+    start: Coords,
+    end: Coords,
+}
+
+fn parse_maze(input: &str) -> Maze {
+    let (walls, markers) = MarkerSpec::new(b'.')
+        .exactly_one([b'S'])
+        .exactly_one([b'E'])
+        .parse(input.lines(), |_coords, byte| match byte {
+            b'#' => true,
+            b'.' => false,
+            _ => panic!("unrecognized tile {:?}", char::from(byte)),
+        });
+    Maze {
+        walls,
+        start: markers.get(b'S')[0],
+        end: markers.get(b'E')[0],
+    }
+}
+
+/// Returns the lowest score, and the number of tiles on any path with that score.
+fn lowest_score_and_best_tiles(input: &str, use_heuristic: bool) -> (u64, usize) {
+    let Maze { walls, start, end } = parse_maze(input);
+    let bounds = walls.size();
+
+    let successors = |&state: &(Coords, Direction)| {
+        oriented_grid_successors(bounds, state, 1, 1000, |coords| !walls[coords])
+    };
+    let is_goal = |&(coords, _direction): &(Coords, Direction)| coords == end;
+    let starts = [(start, Direction::East)];
+    let result = if use_heuristic {
+        // NOTE: Every step costs at least 1, so Manhattan distance never overestimates.
+        let heuristic = |&(coords, _direction): &(Coords, Direction)| {
+            let (row, col) = coords.into_row_major();
+            let (end_row, end_col) = end.into_row_major();
+            u64::try_from(row.abs_diff(end_row) + col.abs_diff(end_col)).unwrap()
+        };
+        astar(starts, successors, heuristic, is_goal, Predecessors::All)
+    } else {
+        dijkstra(starts, successors, is_goal, Predecessors::All)
+    };
+
+    let lowest_score = result.distance(&result.goals()[0]).unwrap();
+    assert!(result
+        .goals()
+        .iter()
+        .all(|goal| result.distance(goal) == Some(lowest_score)));
+
+    let path = result.path().unwrap();
+    assert_eq!(path.first(), Some(&starts[0]));
+    assert_eq!(path.last().map(|&(coords, _direction)| coords), Some(end));
+
+    let best_tiles = result
+        .states_on_shortest_paths()
+        .into_iter()
+        .map(|(coords, _direction)| coords)
+        .collect::<HashSet<_>>();
+    (lowest_score, best_tiles.len())
+}
+
+#[test]
+fn p1_example() {
+    for use_heuristic in [false, true] {
+        assert_eq!(
+            lowest_score_and_best_tiles(EXAMPLE_1, use_heuristic).0,
+            7036
+        );
+        assert_eq!(
+            lowest_score_and_best_tiles(EXAMPLE_2, use_heuristic).0,
+            11048
+        );
+    }
+}
+
+#[test]
+fn p2_example() {
+    for use_heuristic in [false, true] {
+        assert_eq!(lowest_score_and_best_tiles(EXAMPLE_1, use_heuristic).1, 45);
+        assert_eq!(lowest_score_and_best_tiles(EXAMPLE_2, use_heuristic).1, 64);
+    }
+}
+
+/// Ignoring turns, returns the fewest steps from the start to the end, and the number of tiles
+/// on any path with that many steps.
+fn fewest_steps_and_shortest_path_tiles(input: &str, predecessors: Predecessors) -> (u64, usize) {
+    let Maze { walls, start, end } = parse_maze(input);
+    let bounds = walls.size();
+
+    let result = bfs(
+        [start],
+        |&coords| grid_successors(bounds, coords, |coords| !walls[coords]),
+        |&coords| coords == end,
+        predecessors,
+    );
+    let path = result.path().unwrap();
+    assert_eq!(path.first(), Some(&start));
+    assert_eq!(path.last(), Some(&end));
+    assert!(path
+        .windows(2)
+        .all(|pair| grid_successors(bounds, pair[0], |_coords| true).any(|next| next == pair[1])));
+
+    let fewest_steps = result.distance(&end).unwrap();
+    assert_eq!(u64::try_from(path.len()).unwrap(), fewest_steps + 1);
+    (fewest_steps, result.states_on_shortest_paths().len())
+}
+
+#[test]
+fn bfs_example() {
+    assert_eq!(
+        fewest_steps_and_shortest_path_tiles(EXAMPLE_1, Predecessors::All),
+        (28, 37)
+    );
+    assert_eq!(
+        fewest_steps_and_shortest_path_tiles(EXAMPLE_2, Predecessors::All),
+        (40, 41)
+    );
+    assert_eq!(
+        fewest_steps_and_shortest_path_tiles(EXAMPLE_1, Predecessors::First).0,
+        28
+    );
+}
@@ -1,6 +1,6 @@
-use std::collections::{HashMap, HashSet};
-
-use itertools::Itertools;
+use advent_of_code_2024::ordering::{
+    BeforeAfterRules, PrecedenceCycle, PrecedenceGraph, UpdateDiagnostics,
+};
 
 const EXAMPLE: &str = "\
 47|53
@@ -33,34 +33,11 @@ const EXAMPLE: &str = "\
 97,13,75,29,47
 ";
 
-pub(crate) struct BeforeAfterRules {
-    inner: HashMap<u8, HashSet<u8>>,
-}
-
-impl BeforeAfterRules {
-    pub fn breaks_with(&self, before: u8, after: u8) -> bool {
-        let Self { inner } = self;
-        inner
-            .get(&after)
-            .is_some_and(|needs_after| needs_after.contains(&before))
-    }
-}
-
 fn parse_p1(input: &str) -> (BeforeAfterRules, impl Iterator<Item = Vec<u8>> + '_) {
     let mut lines = input.lines();
 
-    let mut before_after_rules = HashMap::<_, HashSet<_>>::new();
-    lines
-        .by_ref()
-        .take_while(|line| !line.trim().is_empty())
-        .for_each(|line| {
-            let (before, after) = line
-                .splitn(2, "|")
-                .map(|x| x.parse().unwrap())
-                .collect_tuple()
-                .expect("expected rule with `|`");
-            before_after_rules.entry(before).or_default().insert(after);
-        });
+    let before_after_rules =
+        BeforeAfterRules::parse(lines.by_ref().take_while(|line| !line.trim().is_empty())).unwrap();
 
     let updates = lines.map(|l| {
         l.split(",")
@@ -68,31 +45,30 @@ fn parse_p1(input: &str) -> (BeforeAfterRules, impl Iterator<Item = Vec<u8>> + '
             .collect::<Vec<u8>>()
     });
 
-    (
-        BeforeAfterRules {
-            inner: before_after_rules,
-        },
-        updates,
-    )
+    (before_after_rules, updates)
+}
+
+/// The instructions say nothing about updates with an even number of pages, so make sure that we
+/// never have to guess at what their "middle" is.
+#[track_caller]
+fn middle_page(update: &[u8]) -> u8 {
+    assert!(
+        update.len() % 2 == 1,
+        "{update:?} has an even number of pages, so it has no middle page"
+    );
+    update[update.len() / 2]
 }
 
 fn correct_middle_page_number_sum(input: &str) -> u32 {
     let (before_after_rules, updates) = parse_p1(input);
 
     let mut middle_page_number_sum = 0u32;
-    'next_row: for update in updates {
-        let mut update_iter = update.iter().copied();
-        while let Some(value) = update_iter.next() {
-            for other_value in update_iter.clone() {
-                if before_after_rules.breaks_with(value, other_value) {
-                    continue 'next_row;
-                }
-            }
+    for update in updates {
+        if !before_after_rules.is_correctly_ordered(&update) {
+            continue;
         }
-        // TODO: Is this indexing right? The instructions say nothing about
-        // even-numbered update rows. 🫤
         middle_page_number_sum = middle_page_number_sum
-            .checked_add(update[update.len() / 2].into())
+            .checked_add(middle_page(&update).into())
             .unwrap();
     }
     middle_page_number_sum
@@ -115,29 +91,13 @@ fn incorrect_middle_page_number_sum(input: &str) -> u32 {
 
     let mut incorrect_middle_page_number_sum = 0u32;
     for mut update in updates {
-        let mut needed_correction = false;
-        let mut value_idx = 0;
-        'outer: while value_idx < update.len() {
-            let mut other_value_idx = value_idx + 1;
-            while other_value_idx < update.len() {
-                let other_value = update[other_value_idx];
-                if before_after_rules.breaks_with(update[value_idx], other_value) {
-                    needed_correction = true;
-                    update.remove(other_value_idx);
-                    update.insert(value_idx, other_value);
-                    continue 'outer;
-                }
-                other_value_idx += 1;
-            }
-            value_idx += 1;
-        }
-        // TODO: Is this indexing right? The instructions say nothing about
-        // even-numbered update rows. 🫤
-        if needed_correction {
-            incorrect_middle_page_number_sum = incorrect_middle_page_number_sum
-                .checked_add(update[update.len() / 2].into())
-                .unwrap();
+        if before_after_rules.is_correctly_ordered(&update) {
+            continue;
         }
+        before_after_rules.sort(&mut update).unwrap();
+        incorrect_middle_page_number_sum = incorrect_middle_page_number_sum
+            .checked_add(middle_page(&update).into())
+            .unwrap();
     }
     incorrect_middle_page_number_sum
 }
@@ -147,6 +107,61 @@ fn p2_example() {
     assert_eq!(incorrect_middle_page_number_sum(EXAMPLE), 123);
 }
 
+#[test]
+fn contradictory_rules() {
+    let rules = PrecedenceGraph::parse_rules(["47|53", "53|29", "29|47", "97|47"]).unwrap();
+
+    // NOTE: 97 and 53 are only related through 47, which isn't in the subset, so the smallest
+    // goes first.
+    assert_eq!(rules.topological_order([97, 53]), Ok(vec![53, 97]));
+    assert_eq!(rules.topological_order([53, 97, 53]), Ok(vec![53, 97]));
+
+    let cycle = rules.topological_order([97, 47, 53, 29]).unwrap_err();
+    assert_eq!(
+        cycle,
+        PrecedenceCycle {
+            items: vec![47, 53, 29]
+        }
+    );
+    assert_eq!(
+        cycle.to_string(),
+        "contradictory rules: 47|53, 53|29, 29|47"
+    );
+
+    let before_after_rules = BeforeAfterRules::new(rules);
+    let mut update = vec![29, 97, 53, 47];
+    assert_eq!(before_after_rules.sort(&mut update), Err(cycle));
+    assert_eq!(update, [29, 97, 53, 47], "shouldn't change on failure");
+
+    // NOTE: Duplicates stay, and unrelated pages are ordered as in `topological_order`.
+    let mut update = vec![97, 53, 97, 29];
+    assert_eq!(before_after_rules.sort(&mut update), Ok(()));
+    assert_eq!(update, [53, 29, 97, 97]);
+}
+
+#[test]
+fn diagnostics() {
+    for input in [EXAMPLE, INPUT] {
+        let (before_after_rules, mut updates) = parse_p1(input);
+        assert!(updates.all(|update| before_after_rules.diagnose(&update).is_clean()));
+    }
+
+    let (before_after_rules, _updates) = parse_p1(EXAMPLE);
+    let mut update = vec![97, 13, 75, 29, 47];
+    before_after_rules.checked_sort(&mut update);
+    assert_eq!(update, [97, 75, 47, 29, 13]);
+
+    let before_after_rules = BeforeAfterRules::parse(["1|2", "2|3", "3|1", "4|1"]).unwrap();
+    assert_eq!(
+        before_after_rules.diagnose(&[4, 3, 2, 1]),
+        UpdateDiagnostics {
+            even_length: true,
+            unrelated_pairs: vec![(4, 3), (4, 2)],
+            non_transitive_triples: vec![(3, 1, 2)],
+        }
+    );
+}
+
 #[test]
 fn p2() {
     assert_eq!(incorrect_middle_page_number_sum(INPUT), 4151);
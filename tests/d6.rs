@@ -1,9 +1,16 @@
-use std::collections::{HashMap, HashSet};
-
 use advent_of_code_2024::{
-    search_direction::{SearchDirection, Sign},
-    uniform_width_ascii_lines,
+    collections::CoordSet,
+    direction::Direction,
+    grid::{
+        markers::{MarkerError, MarkerSpec},
+        Grid,
+    },
+    render::Canvas,
+    simulate::{find_cycle_brent, Outcome},
+    space::d2::{Coords, Size},
+    GridShapeError, GridShapeErrorKind,
 };
+use insta::assert_snapshot;
 
 const EXAMPLE: &str = "\
 ....#.....
@@ -18,25 +25,6 @@ const EXAMPLE: &str = "\
 ......#...
 ";
 
-#[derive(Clone, Copy, Debug, Eq, PartialEq)]
-enum GuardDirection {
-    Up,
-    Right,
-    Down,
-    Left,
-}
-
-impl GuardDirection {
-    pub fn next(self) -> Self {
-        match self {
-            Self::Up => Self::Right,
-            Self::Right => Self::Down,
-            Self::Down => Self::Left,
-            Self::Left => Self::Up,
-        }
-    }
-}
-
 #[derive(Debug)]
 enum Tile {
     Obstacle,
@@ -45,76 +33,134 @@ enum Tile {
 
 struct ParsedInput {
     guard: Guard,
-    grid: Vec<Vec<Tile>>,
+    grid: Grid<Tile>,
 }
 
 fn parse_grid(input: &str) -> ParsedInput {
-    let mut guard_position = None;
-    let grid = uniform_width_ascii_lines(input.lines())
-        .enumerate()
-        .map(|(row_idx, line)| {
-            line.chars()
-                .enumerate()
-                .map(|(col_idx, c)| match c {
-                    '#' => Tile::Obstacle,
-                    '.' => Tile::Empty,
-                    '^' => {
-                        assert!(
-                            guard_position.replace((row_idx, col_idx)).is_none(),
-                            "multiple guard positions found"
-                        );
-                        Tile::Empty
-                    }
-                    _ => panic!("unrecognized tile {c:?}"),
-                })
-                .collect::<Vec<_>>()
-        })
-        .collect::<Vec<_>>();
-
-    let guard_position = guard_position.expect("no guard position found");
+    let (grid, markers) = MarkerSpec::new(b'.').exactly_one(*b"^>v<").parse(
+        input.lines(),
+        |_coords, byte| match byte {
+            b'#' => Tile::Obstacle,
+            b'.' => Tile::Empty,
+            _ => panic!("unrecognized tile {:?}", char::from(byte)),
+        },
+    );
+
+    let (marker, position) = markers.iter().next().unwrap();
+    let direction = Direction::from_char(char::from(marker)).unwrap();
+    let guard = Guard::new(position, direction);
 
-    let guard = Guard::new(guard_position);
     ParsedInput { guard, grid }
 }
 
+#[test]
+fn parsing_errors() {
+    let try_parse = |input: &'static str| {
+        MarkerSpec::new(b'.')
+            .exactly_one(*b"^>v<")
+            .try_parse(input.lines(), |_coords, byte| byte)
+            .map(|_| ())
+            .map_err(|e| e.to_string())
+    };
+
+    assert_eq!(
+        try_parse("..#\n...\n"),
+        Err("expected exactly one of '^', '>', 'v', '<', but found none".to_owned())
+    );
+    assert_eq!(
+        try_parse(".^#\n..<\n"),
+        Err(
+            "expected exactly one of '^', '>', 'v', '<', but found 2: '^' at (0, 1), '<' at (1, 2)"
+                .to_owned()
+        )
+    );
+    assert_eq!(
+        try_parse("..#\n.^\n"),
+        Err("line 2 has width 2, but the first line's width is 3".to_owned())
+    );
+    assert_eq!(
+        MarkerSpec::new(b'.')
+            .exactly_one(*b"^")
+            .try_parse("...\n.é.\n".lines(), |_coords, byte| byte)
+            .map(|_| ()),
+        Err(MarkerError::Shape(GridShapeError {
+            line_num: 2,
+            kind: GridShapeErrorKind::NonAscii { byte_offset: 1 },
+        }))
+    );
+
+    let try_parse_at_most_one = |input: &'static str| {
+        MarkerSpec::new(b'.')
+            .exactly_one(*b"S")
+            .at_most_one(*b"E")
+            .try_parse(input.lines(), |_coords, byte| byte)
+            .map(|(_grid, markers)| markers.iter().collect::<Vec<_>>())
+            .map_err(|e| e.to_string())
+    };
+    assert_eq!(
+        try_parse_at_most_one("S.\n..\n"),
+        Ok(vec![(b'S', Coords::from_row_major((0, 0)))])
+    );
+    assert_eq!(
+        try_parse_at_most_one("S.\n.E\n"),
+        Ok(vec![
+            (b'E', Coords::from_row_major((1, 1))),
+            (b'S', Coords::from_row_major((0, 0))),
+        ])
+    );
+    assert_eq!(
+        try_parse_at_most_one("SE\n.E\n"),
+        Err("expected at most one of 'E', but found 2: 'E' at (0, 1), 'E' at (1, 1)".to_owned())
+    );
+}
+
+#[test]
+#[should_panic(expected = "'^' is already a marker")]
+fn duplicate_markers() {
+    MarkerSpec::new(b'.')
+        .exactly_one(*b"^>")
+        .at_most_one(*b"v^");
+}
+
+#[test]
+#[should_panic(expected = "'^' is already a marker")]
+fn duplicate_markers_in_one_rule() {
+    MarkerSpec::new(b'.').exactly_one(*b"^>^");
+}
+
+#[test]
+#[should_panic(expected = "'.' is the background tile, so it can't be a marker")]
+fn background_marker() {
+    MarkerSpec::new(b'.').any_number(*b"#.");
+}
+
 #[derive(Clone, Debug, Eq, PartialEq)]
 struct Guard {
-    pub position: (usize, usize),
-    pub direction: GuardDirection,
+    pub position: Coords,
+    pub direction: Direction,
 }
 
 impl Guard {
-    pub fn new(position: (usize, usize)) -> Self {
+    pub fn new(position: Coords, direction: Direction) -> Self {
         Self {
             position,
-            direction: GuardDirection::Up,
+            direction,
         }
     }
 
-    fn front_facing_tile(&self, bounds: (usize, usize)) -> Option<(usize, usize)> {
-        let search_direction = {
-            let (horizontal, vertical) = match self.direction {
-                GuardDirection::Up => (Sign::Neutral, Sign::Negative),
-                GuardDirection::Right => (Sign::Positive, Sign::Neutral),
-                GuardDirection::Down => (Sign::Neutral, Sign::Positive),
-                GuardDirection::Left => (Sign::Negative, Sign::Neutral),
-            };
-            SearchDirection {
-                horizontal,
-                vertical,
-            }
-        };
-
-        search_direction.to_2d_offsets(self.position, bounds, 1)
+    fn front_facing_tile(&self, bounds: Size) -> Option<Coords> {
+        self.direction
+            .to_search_direction()
+            .to_2d_offsets(self.position, bounds, 1)
     }
 
-    pub fn make_next_move(&mut self, grid: &[Vec<Tile>]) -> Option<(usize, usize)> {
+    pub fn make_next_move(&mut self, grid: &Grid<Tile>) -> Option<Coords> {
         let start_direction = self.direction;
         loop {
-            match self.front_facing_tile((grid.len(), grid[0].len())) {
-                Some(position @ (row_idx, col_idx)) => match grid[row_idx][col_idx] {
+            match self.front_facing_tile(grid.size()) {
+                Some(position) => match grid[position] {
                     Tile::Obstacle => {
-                        self.direction = self.direction.next();
+                        self.direction = self.direction.turn_right();
                         if self.direction == start_direction {
                             break None;
                         }
@@ -130,22 +176,19 @@ impl Guard {
     }
 }
 
-fn guard_patrol_tiles_visited(input: &str) -> usize {
-    let ParsedInput { mut guard, grid } = parse_grid(input);
+fn guard_patrol_positions(mut guard: Guard, grid: &Grid<Tile>) -> CoordSet {
+    let mut guard_positions_visited = CoordSet::new(grid.size());
 
-    let mut guard_positions_visited = HashMap::<usize, HashSet<usize>>::new();
-
-    while let Some((row_idx, col_idx)) = guard.make_next_move(&grid) {
-        guard_positions_visited
-            .entry(row_idx)
-            .or_default()
-            .insert(col_idx);
+    while let Some(position) = guard.make_next_move(grid) {
+        guard_positions_visited.insert(position);
     }
 
     guard_positions_visited
-        .values()
-        .map(|p| p.len())
-        .sum::<usize>()
+}
+
+fn guard_patrol_tiles_visited(input: &str) -> usize {
+    let ParsedInput { guard, grid } = parse_grid(input);
+    guard_patrol_positions(guard, &grid).len()
 }
 
 #[test]
@@ -153,6 +196,17 @@ fn p1_example() {
     assert_eq!(guard_patrol_tiles_visited(EXAMPLE), 41);
 }
 
+#[test]
+fn p1_example_rendering() {
+    let ParsedInput { guard, grid } = parse_grid(EXAMPLE);
+    let mut canvas = Canvas::from_grid(&grid, |tile| match tile {
+        Tile::Obstacle => '#',
+        Tile::Empty => '.',
+    });
+    canvas.draw_set(&guard_patrol_positions(guard, &grid), 'X');
+    assert_snapshot!(canvas);
+}
+
 const INPUT: &str = include_str!("./d6.txt");
 
 #[test]
@@ -161,72 +215,31 @@ fn p1() {
 }
 
 fn num_forever_obstacle_positions_for_guard_patrol(input: &str) -> usize {
-    let ParsedInput {
-        mut guard,
-        mut grid,
-    } = parse_grid(input);
-
-    let mut forever_obstacle_positions = HashMap::<usize, HashSet<usize>>::new();
-    let original_guard_state = guard.clone();
-
-    for obstacle_row_idx in 0..grid.len() {
-        for obstacle_col_idx in 0..(grid[0].len()) {
-            macro_rules! obstacle_cell {
-                () => {
-                    &mut grid[obstacle_row_idx][obstacle_col_idx]
-                };
-            }
-            match obstacle_cell!() {
-                Tile::Obstacle => continue,
-                Tile::Empty => {
-                    if (obstacle_row_idx, obstacle_col_idx) == original_guard_state.position {
-                        continue;
-                    }
-                }
-            }
+    let ParsedInput { guard, mut grid } = parse_grid(input);
 
-            guard = original_guard_state.clone();
-            *obstacle_cell!() = Tile::Obstacle;
-            {
-                let mut visited = HashMap::<usize, HashMap<usize, Guard>>::new();
-                loop {
-                    let should_break =
-                        guard
-                            .make_next_move(&grid)
-                            .is_none_or(|(row_idx, col_idx)| {
-                                use std::collections::hash_map::Entry;
-                                let visited_in_same_orientation =
-                                    match visited.entry(row_idx).or_default().entry(col_idx) {
-                                        Entry::Occupied(occupied_entry) => {
-                                            occupied_entry.get() == &guard
-                                        }
-                                        Entry::Vacant(entry) => {
-                                            entry.insert(guard.clone());
-                                            false
-                                        }
-                                    };
-                                if visited_in_same_orientation {
-                                    forever_obstacle_positions
-                                        .entry(obstacle_row_idx)
-                                        .or_default()
-                                        .insert(obstacle_col_idx);
-                                }
-                                visited_in_same_orientation
-                            });
-
-                    if should_break {
-                        break;
-                    }
-                }
-            }
-            *obstacle_cell!() = Tile::Empty;
+    // NOTE: An obstacle can only change the guard's patrol if it's somewhere they would have
+    // walked.
+    let obstacle_candidates = guard_patrol_positions(guard.clone(), &grid);
+
+    let mut num_forever_obstacle_positions = 0;
+    for obstacle_coords in obstacle_candidates.iter() {
+        if obstacle_coords == guard.position {
+            continue;
+        }
+
+        grid[obstacle_coords] = Tile::Obstacle;
+        let outcome = find_cycle_brent(guard.clone(), |guard| {
+            let mut guard = guard.clone();
+            guard.make_next_move(&grid)?;
+            Some(guard)
+        });
+        if let Outcome::Cycled(_) = outcome {
+            num_forever_obstacle_positions += 1;
         }
+        grid[obstacle_coords] = Tile::Empty;
     }
 
-    forever_obstacle_positions
-        .values()
-        .map(|ps| ps.len())
-        .sum::<usize>()
+    num_forever_obstacle_positions
 }
 
 #[test]
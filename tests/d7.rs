@@ -1,6 +1,7 @@
-use std::iter::repeat_n;
-
-use itertools::Itertools;
+use advent_of_code_2024::calibration::{self, Inverse, Operation};
+use insta::assert_snapshot;
+use itertools::Itertools as _;
+use strum::IntoEnumIterator as _;
 
 const EXAMPLE: &str = "\
 190: 10 19
@@ -26,49 +27,63 @@ fn parse_equations(input: &str) -> impl Iterator<Item = (u64, Vec<u64>)> + '_ {
     })
 }
 
-trait Operation: strum::IntoEnumIterator + Clone {
-    fn execute(&self, lhs: u64, rhs: u64) -> u64;
-}
-
 fn total_calibration_result<Op>(input: &str) -> u64
 where
     Op: Operation,
 {
     parse_equations(input)
         .filter_map(|(test_value, terms)| {
-            let test_value_synthesizable_from_terms = match terms.len() {
-                0 => false,
-                1 => terms[0] == test_value,
-                _multiple => repeat_n(Op::iter(), terms.len() - 1)
-                    .multi_cartesian_product()
-                    .any(|operators| {
-                        let mut terms_iter = terms.iter().copied();
-                        let mut acc = terms_iter.next().unwrap();
-                        for (op, term) in operators.iter().zip_eq(terms_iter) {
-                            acc = op.execute(acc, term)
-                        }
-                        acc == test_value
-                    }),
-            };
-            test_value_synthesizable_from_terms.then_some(test_value)
+            let ops = calibration::solve::<Op>(test_value, &terms)?;
+            debug_assert_eq!(calibration::evaluate(&terms, &ops), test_value);
+            Some(test_value)
         })
         .sum::<u64>()
 }
 
-#[derive(Clone, Copy, Debug, strum::EnumIter)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq, strum::EnumIter)]
 enum OperationP1 {
     Mul,
     Add,
 }
 
 impl Operation for OperationP1 {
-    #[track_caller]
-    fn execute(&self, lhs: u64, rhs: u64) -> u64 {
+    fn symbol(&self) -> &'static str {
         match self {
-            OperationP1::Mul => lhs.checked_mul(rhs).unwrap(),
-            OperationP1::Add => lhs.checked_add(rhs).unwrap(),
+            OperationP1::Mul => "*",
+            OperationP1::Add => "+",
         }
     }
+
+    fn checked_execute(&self, lhs: u64, rhs: u64) -> Option<u64> {
+        match self {
+            OperationP1::Mul => lhs.checked_mul(rhs),
+            OperationP1::Add => lhs.checked_add(rhs),
+        }
+    }
+
+    fn unexecute(&self, result: u64, rhs: u64) -> Inverse {
+        match self {
+            OperationP1::Mul => match (result, rhs) {
+                (0, 0) => Inverse::Any,
+                (_, 0) => Inverse::None,
+                _ if result % rhs == 0 => Inverse::Unique(result / rhs),
+                _ => Inverse::None,
+            },
+            OperationP1::Add => result
+                .checked_sub(rhs)
+                .map_or(Inverse::None, Inverse::Unique),
+        }
+    }
+}
+
+/// Explains how each equation in `input` is (or isn't) satisfied, one per line.
+fn explain_calibration<Op>(input: &str) -> String
+where
+    Op: Operation,
+{
+    parse_equations(input)
+        .map(|(test_value, terms)| format!("{}\n", calibration::explain::<Op>(test_value, &terms)))
+        .collect()
 }
 
 #[test]
@@ -76,6 +91,11 @@ fn p1_example() {
     assert_eq!(total_calibration_result::<OperationP1>(EXAMPLE), 3749);
 }
 
+#[test]
+fn p1_example_explanation() {
+    assert_snapshot!(explain_calibration::<OperationP1>(EXAMPLE));
+}
+
 const INPUT: &str = include_str!("./d7.txt");
 
 #[test]
@@ -86,7 +106,7 @@ fn p1() {
     );
 }
 
-#[derive(Clone, Copy, Debug, strum::EnumIter)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq, strum::EnumIter)]
 enum OperationP2 {
     Mul,
     Add,
@@ -94,16 +114,39 @@ enum OperationP2 {
 }
 
 impl Operation for OperationP2 {
-    fn execute(&self, lhs: u64, rhs: u64) -> u64 {
+    fn symbol(&self) -> &'static str {
+        match self {
+            OperationP2::Mul => OperationP1::Mul.symbol(),
+            OperationP2::Add => OperationP1::Add.symbol(),
+            OperationP2::Concat => "||",
+        }
+    }
+
+    fn checked_execute(&self, lhs: u64, rhs: u64) -> Option<u64> {
+        match self {
+            OperationP2::Mul => OperationP1::Mul.checked_execute(lhs, rhs),
+            OperationP2::Add => OperationP1::Add.checked_execute(lhs, rhs),
+            OperationP2::Concat => {
+                let power_of_ten = 10u64.checked_pow(rhs.checked_ilog10().unwrap_or(0) + 1)?;
+                lhs.checked_mul(power_of_ten)?.checked_add(rhs)
+            }
+        }
+    }
+
+    fn unexecute(&self, result: u64, rhs: u64) -> Inverse {
         match self {
-            OperationP2::Mul => OperationP1::Mul.execute(lhs, rhs),
-            OperationP2::Add => OperationP1::Add.execute(lhs, rhs),
+            OperationP2::Mul => OperationP1::Mul.unexecute(result, rhs),
+            OperationP2::Add => OperationP1::Add.unexecute(result, rhs),
             OperationP2::Concat => {
-                let power_of_ten = rhs.ilog10() + 1;
-                lhs.checked_mul(10u64.checked_pow(power_of_ten).unwrap())
-                    .unwrap()
-                    .checked_add(rhs)
-                    .unwrap()
+                let Some(power_of_ten) = 10u64.checked_pow(rhs.checked_ilog10().unwrap_or(0) + 1)
+                else {
+                    return Inverse::None;
+                };
+                if result % power_of_ten == rhs {
+                    Inverse::Unique(result / power_of_ten)
+                } else {
+                    Inverse::None
+                }
             }
         }
     }
@@ -115,7 +158,95 @@ fn p2_example() {
 }
 
 #[test]
-#[ignore]
+fn zero_terms() {
+    use OperationP1::*;
+
+    assert_eq!(
+        calibration::solve::<OperationP1>(0, &[5, 0]),
+        Some(vec![Mul])
+    );
+    assert_eq!(
+        calibration::solve::<OperationP1>(0, &[3, 4, 0]),
+        Some(vec![Mul, Mul])
+    );
+    assert_eq!(
+        calibration::solve::<OperationP1>(7, &[3, 0, 4]),
+        Some(vec![Add, Add])
+    );
+    assert_eq!(calibration::solve::<OperationP1>(1, &[5, 0]), None);
+
+    // NOTE: Multiplying the first two terms would overflow before the multiplication by 0.
+    let big = 10_000_000_000;
+    let ops = calibration::solve::<OperationP1>(0, &[big, big, 0]);
+    assert_eq!(ops, Some(vec![Add, Mul]));
+    assert_eq!(calibration::evaluate(&[big, big, 0], &ops.unwrap()), 0);
+    assert_eq!(
+        calibration::solve::<OperationP1>(0, &[u64::MAX, 1, 0]),
+        Some(vec![Mul, Mul])
+    );
+    assert_eq!(
+        calibration::solve::<OperationP1>(0, &[u64::MAX, 2, 0]),
+        None,
+        "every way of combining the first two terms overflows"
+    );
+    assert_eq!(
+        total_calibration_result::<OperationP1>("0: 5 0\n1: 5 0\n12: 3 0 4\n"),
+        12
+    );
+}
+
+#[test]
+fn p2_example_explanation() {
+    assert_snapshot!(explain_calibration::<OperationP2>(EXAMPLE));
+}
+
+#[test]
+fn explanations_agree_with_solver() {
+    let input = format!("{EXAMPLE}0: 5 0\n1: 5 0\n12: 3 0 4\n");
+    for (test_value, terms) in parse_equations(&input) {
+        let explanation = calibration::explain::<OperationP2>(test_value, &terms);
+        let ops = calibration::solve::<OperationP2>(test_value, &terms);
+        assert_eq!(explanation.is_solved(), ops.is_some(), "{explanation}");
+        if !explanation.is_solved() {
+            continue;
+        }
+        assert_eq!(calibration::evaluate(&terms, &explanation.ops), test_value);
+
+        // The first solution in left-to-right order
+        let first = std::iter::repeat_n(OperationP2::iter(), terms.len() - 1)
+            .multi_cartesian_product()
+            .find(|ops| {
+                terms[1..]
+                    .iter()
+                    .zip(ops)
+                    .try_fold(terms[0], |acc, (&term, op)| op.checked_execute(acc, term))
+                    == Some(test_value)
+            });
+        assert_eq!(Some(explanation.ops), first);
+    }
+
+    use OperationP1::*;
+    let explanation = calibration::explain::<OperationP1>(3267, &[81, 40, 27]);
+    assert_eq!(explanation.ops, [Mul, Add]);
+    assert_eq!(explanation.to_string(), "3267 = 81 * 40 + 27");
+}
+
+#[test]
+fn p2_example_operators() {
+    use OperationP2::*;
+
+    assert_eq!(
+        calibration::solve::<OperationP2>(7290, &[6, 8, 6, 15]),
+        Some(vec![Mul, Concat, Mul])
+    );
+    assert_eq!(
+        calibration::solve::<OperationP2>(192, &[17, 8, 14]),
+        Some(vec![Concat, Add])
+    );
+    assert_eq!(calibration::solve::<OperationP2>(83, &[17, 5]), None);
+}
+
+#[test]
 fn p2() {
     assert_eq!(
         total_calibration_result::<OperationP2>(INPUT),
@@ -1,12 +1,10 @@
-use std::{
-    collections::{HashMap, HashSet},
-    iter,
-};
-
 use advent_of_code_2024::{
-    space::{d2::Size, Coord},
-    uniform_width_ascii_lines,
+    collections::CoordSet,
+    grid::{markers::MarkerSpec, Grid},
+    render::Canvas,
+    space::d2::{Coords, Size},
 };
+use insta::assert_snapshot;
 use itertools::Itertools;
 
 const EXAMPLE_P1: &str = "\
@@ -24,64 +22,35 @@ const EXAMPLE_P1: &str = "\
 ............
 ";
 
-fn antennae_by_frequency(input: &str) -> (Size, HashMap<u8, HashMap<Coord, HashSet<Coord>>>) {
-    let lines =
-        uniform_width_ascii_lines(input.lines()).map(|line| line.as_bytes().iter().copied());
-
-    let grid_size = {
-        let grid_height = lines.clone().count();
-        let grid_width = lines.clone().next().unwrap().len();
-
-        Size::from_row_major((grid_height, grid_width))
-    };
-
-    let mut antennae_by_frequency = HashMap::<_, HashMap<_, HashSet<_>>>::new();
-
-    for (row_idx, line) in lines.enumerate() {
-        for (col_idx, cell_value) in line.enumerate() {
-            assert!(cell_value.is_ascii_graphic());
-            match cell_value {
-                b'.' => (),
-                b'#' => {
-                    static EMITTED_WARNING: std::sync::atomic::AtomicBool =
-                        std::sync::atomic::AtomicBool::new(false);
-                    if !EMITTED_WARNING.swap(true, std::sync::atomic::Ordering::AcqRel) {
-                        eprintln!("WARNING: ignoring '#' (antinode) tiles");
-                    }
-                }
-                antenna_frequency => {
-                    antennae_by_frequency
-                        .entry(antenna_frequency)
-                        .or_default()
-                        .entry(Coord::new(row_idx))
-                        .or_default()
-                        .insert(Coord::new(col_idx));
-                }
-            }
-        }
-    }
-
-    (grid_size, antennae_by_frequency)
+const ANTINODE: u8 = b'#';
+
+/// Antenna positions, grouped by frequency.
+fn antennae_by_frequency(input: &str) -> (Size, Vec<(u8, Vec<Coords>)>) {
+    let (grid, markers) = MarkerSpec::new(b'.')
+        .any_number((b'0'..=b'9').chain(b'A'..=b'Z').chain(b'a'..=b'z'))
+        // NOTE: Some examples have antinodes drawn in, but they don't matter for the answer.
+        .any_number([ANTINODE])
+        .parse(input.lines(), |_coords, byte| {
+            assert_eq!(byte, b'.', "unrecognized tile {:?}", char::from(byte))
+        });
+
+    let antennae_by_frequency = markers
+        .by_marker()
+        .filter(|&(marker, _positions)| marker != ANTINODE)
+        .map(|(frequency, positions)| (frequency, positions.to_vec()))
+        .collect();
+
+    (grid.size(), antennae_by_frequency)
 }
 
-fn calculate_antinodes_p1(input: &str) -> usize {
-    use advent_of_code_2024::space::d2::{apply_rel_offset, Coords};
+fn antinode_locations_p1(input: &str) -> CoordSet {
+    use advent_of_code_2024::space::d2::apply_rel_offset;
 
     let (grid_size, antennae_by_frequency) = antennae_by_frequency(input);
-    let antennae_by_frequency = antennae_by_frequency.iter().map(|(freq, locs)| {
-        (
-            freq,
-            locs.iter().flat_map(|(row_idx, cols)| {
-                cols.iter()
-                    .copied()
-                    .map(|col| Coords { row: *row_idx, col })
-            }),
-        )
-    });
-
-    let mut antinodes_by_location = HashMap::<_, HashMap<_, HashSet<_>>>::new();
-    for (frequency, coordinate_pairs) in antennae_by_frequency {
-        for (first, second) in coordinate_pairs.tuple_combinations() {
+
+    let mut antinode_locations = CoordSet::new(grid_size);
+    for (_frequency, coordinate_pairs) in antennae_by_frequency {
+        for (first, second) in coordinate_pairs.into_iter().tuple_combinations() {
             let antinode_pair_half =
                 |first, second| apply_rel_offset(grid_size, second, first - second);
             for antinode_coords in [
@@ -91,21 +60,24 @@ fn calculate_antinodes_p1(input: &str) -> usize {
             .into_iter()
             .flatten()
             {
-                let Coords { row, col } = antinode_coords;
-                antinodes_by_location
-                    .entry(row)
-                    .or_default()
-                    .entry(col)
-                    .or_default()
-                    .insert(frequency);
+                antinode_locations.insert(antinode_coords);
             }
         }
     }
 
-    antinodes_by_location
-        .values()
-        .map(|cols| cols.len())
-        .sum::<usize>()
+    antinode_locations
+}
+
+fn calculate_antinodes_p1(input: &str) -> usize {
+    antinode_locations_p1(input).len()
+}
+
+/// Draws `antinode_locations` onto `input` as `#`, except where there's an antenna.
+fn render_antinodes(input: &str, antinode_locations: &CoordSet) -> String {
+    let grid = Grid::from_ascii_lines(input.lines(), |_coords, byte| char::from(byte));
+    let mut canvas = Canvas::from_grid(&grid, |&c| c);
+    canvas.draw_with(|coords, c| (c == '.' && antinode_locations.contains(coords)).then_some('#'));
+    canvas.to_string()
 }
 
 #[test]
@@ -113,6 +85,14 @@ fn p1_example() {
     assert_eq!(calculate_antinodes_p1(EXAMPLE_P1), 14);
 }
 
+#[test]
+fn p1_example_rendering() {
+    assert_snapshot!(render_antinodes(
+        EXAMPLE_P1,
+        &antinode_locations_p1(EXAMPLE_P1)
+    ));
+}
+
 const INPUT: &str = include_str!("./d8.txt");
 
 #[test]
@@ -120,56 +100,28 @@ fn p1() {
     assert_eq!(calculate_antinodes_p1(INPUT), 311);
 }
 
-fn calculate_antinodes_p2(input: &str) -> usize {
-    use advent_of_code_2024::space::d2::{apply_rel_offset, Coords};
+fn antinode_locations_p2(input: &str) -> CoordSet {
+    use advent_of_code_2024::space::d2::bidirectional_ray;
 
     let (grid_size, antennae_by_frequency) = antennae_by_frequency(input);
-    let antennae_by_frequency = antennae_by_frequency.iter().map(|(freq, locs)| {
-        (
-            freq,
-            locs.iter().flat_map(|(row_idx, cols)| {
-                cols.iter()
-                    .copied()
-                    .map(|col| Coords { row: *row_idx, col })
-            }),
-        )
-    });
-
-    let mut antinodes_by_location = HashMap::<_, HashMap<_, HashSet<_>>>::new();
-    for (frequency, coordinate_pairs) in antennae_by_frequency {
-        for (first, second) in coordinate_pairs.tuple_combinations() {
-            let antinode_pair_half = |first: Coords, second| {
-                let offset = first - second;
-                let mut multiple = 0;
-                iter::from_fn(move || {
-                    let multiplied_offset = offset.checked_mul(multiple).unwrap();
-                    let ret = apply_rel_offset(grid_size, second, multiplied_offset)?;
-                    multiple += 1;
-                    Some(ret)
-                })
-            };
-            for antinode_coords in [
-                antinode_pair_half(first, second),
-                antinode_pair_half(second, first),
-            ]
-            .into_iter()
-            .flatten()
-            {
-                let Coords { row, col } = antinode_coords;
-                antinodes_by_location
-                    .entry(row)
-                    .or_default()
-                    .entry(col)
-                    .or_default()
-                    .insert(frequency);
+
+    let mut antinode_locations = CoordSet::new(grid_size);
+    for (_frequency, coordinate_pairs) in antennae_by_frequency {
+        for (first, second) in coordinate_pairs.into_iter().tuple_combinations() {
+            // NOTE: Reducing the step ensures that we hit every lattice point on the line, even
+            // those between the two antennae.
+            let step = (first - second).reduced();
+            for antinode_coords in bidirectional_ray(grid_size, second, step) {
+                antinode_locations.insert(antinode_coords);
             }
         }
     }
 
-    antinodes_by_location
-        .values()
-        .map(|cols| cols.len())
-        .sum::<usize>()
+    antinode_locations
+}
+
+fn calculate_antinodes_p2(input: &str) -> usize {
+    antinode_locations_p2(input).len()
 }
 
 const EXAMPLE_P2: &str = "\
@@ -191,6 +143,19 @@ fn p2_example() {
     assert_eq!(calculate_antinodes_p2(EXAMPLE_P1), 34);
 }
 
+#[test]
+fn p2_example_rendering() {
+    // NOTE: The example already has its antinodes drawn in.
+    assert_eq!(
+        render_antinodes(EXAMPLE_P2, &antinode_locations_p2(EXAMPLE_P2)),
+        EXAMPLE_P2
+    );
+    assert_snapshot!(render_antinodes(
+        EXAMPLE_P1,
+        &antinode_locations_p2(EXAMPLE_P1)
+    ));
+}
+
 #[test]
 fn p2() {
     assert_eq!(calculate_antinodes_p2(INPUT), 1115);
@@ -0,0 +1,127 @@
+use advent_of_code_2024::{
+    direction::{Direction, Direction8},
+    space::d2::{RelativeOffset, MOORE_STENCIL, VON_NEUMANN_STENCIL},
+};
+use strum::IntoEnumIterator as _;
+
+#[test]
+fn rotations() {
+    use Direction::*;
+
+    assert_eq!(North.turn_right(), East);
+    assert_eq!(North.turn_left(), West);
+    assert_eq!(West.turn_right(), North);
+    assert_eq!(East.reverse(), West);
+    assert_eq!(South.reverse(), North);
+    assert_eq!(North.rotate_by(-3), East);
+    assert_eq!(North.rotate_by(-6), South);
+    assert_eq!(East.rotate_by(4 * 1000 + 1), South);
+    assert_eq!(East.rotate_by(isize::MAX), North);
+    assert_eq!(East.rotate_by(isize::MIN), East);
+
+    for direction in Direction::iter() {
+        assert_eq!(direction.rotate_by(0), direction);
+        assert_eq!(direction.turn_left().turn_right(), direction);
+        assert_eq!(direction.reverse().reverse(), direction);
+        assert_eq!(direction.turn_right().turn_right(), direction.reverse());
+        assert_eq!(direction.rotate_by(-1), direction.turn_left());
+        assert_eq!(
+            -direction.to_rel_offset(),
+            direction.reverse().to_rel_offset()
+        );
+    }
+
+    use Direction8 as D8;
+    assert_eq!(D8::North.turn_right(), D8::NorthEast);
+    assert_eq!(D8::North.turn_left(), D8::NorthWest);
+    assert_eq!(D8::NorthEast.reverse(), D8::SouthWest);
+    assert_eq!(D8::West.rotate_by(-10), D8::South);
+    assert_eq!(D8::West.rotate_by(8 * 1000 + 3), D8::NorthEast);
+    assert_eq!(D8::North.rotate_by(isize::MAX), D8::NorthWest);
+    assert_eq!(D8::North.rotate_by(isize::MIN), D8::North);
+
+    for direction in Direction8::iter() {
+        assert_eq!(direction.turn_left().turn_right(), direction);
+        assert_eq!(direction.reverse().reverse(), direction);
+        assert_eq!(direction.rotate_by(2), direction.turn_right().turn_right());
+        assert_eq!(
+            -direction.to_rel_offset(),
+            direction.reverse().to_rel_offset()
+        );
+    }
+    for direction in Direction::iter() {
+        assert_eq!(
+            Direction8::from(direction.turn_right()),
+            Direction8::from(direction).rotate_by(2)
+        );
+    }
+}
+
+#[test]
+fn stencils() {
+    assert_eq!(
+        Direction::iter()
+            .map(Direction::to_rel_offset)
+            .collect::<Vec<_>>(),
+        VON_NEUMANN_STENCIL
+    );
+    assert_eq!(
+        Direction8::iter()
+            .map(Direction8::to_rel_offset)
+            .collect::<Vec<_>>(),
+        MOORE_STENCIL
+    );
+
+    let offset = |row, col| RelativeOffset::from((row, col));
+    assert_eq!(Direction::North.to_rel_offset(), offset(-1, 0));
+    assert_eq!(Direction::East.to_rel_offset(), offset(0, 1));
+    assert_eq!(Direction8::NorthEast.to_rel_offset(), offset(-1, 1));
+    assert_eq!(Direction8::SouthEast.to_rel_offset(), offset(1, 1));
+    assert_eq!(Direction8::SouthWest.to_rel_offset(), offset(1, -1));
+    assert_eq!(Direction8::NorthWest.to_rel_offset(), offset(-1, -1));
+
+    for direction in Direction::iter() {
+        assert_eq!(
+            Direction8::from(direction).to_rel_offset(),
+            direction.to_rel_offset()
+        );
+    }
+}
+
+#[test]
+fn rel_offsets_agree_with_search_directions() {
+    for direction in Direction::iter() {
+        assert_eq!(
+            direction.to_search_direction().to_rel_offset(),
+            direction.to_rel_offset(),
+            "{direction:?}"
+        );
+    }
+    for direction in Direction8::iter() {
+        assert_eq!(
+            direction.to_search_direction().to_rel_offset(),
+            direction.to_rel_offset(),
+            "{direction:?}"
+        );
+    }
+}
+
+#[test]
+fn parsing() {
+    for (chars, expected) in [
+        ("^UN", Direction::North),
+        (">RE", Direction::East),
+        ("vDS", Direction::South),
+        ("<LW", Direction::West),
+    ] {
+        for c in chars.chars() {
+            assert_eq!(Direction::from_char(c), Some(expected), "{c:?}");
+            assert_eq!(Direction8::from_char(c), Some(expected.into()), "{c:?}");
+        }
+        assert_eq!(Direction::from_char(expected.to_arrow()), Some(expected));
+    }
+    for c in ['u', 'n', 'V', 'X', '.', ' '] {
+        assert_eq!(Direction::from_char(c), None, "{c:?}");
+        assert_eq!(Direction8::from_char(c), None, "{c:?}");
+    }
+}
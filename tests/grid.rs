@@ -0,0 +1,36 @@
+use advent_of_code_2024::{grid::Grid, GridShapeError, GridShapeErrorKind};
+
+#[test]
+fn parsing_errors() {
+    let try_parse =
+        |input: &'static str| Grid::try_from_ascii_lines(input.lines(), |_coords, byte| byte);
+
+    assert_eq!(
+        try_parse("XMAS\nSAMX\nXMA\n"),
+        Err(GridShapeError {
+            line_num: 3,
+            kind: GridShapeErrorKind::WidthMismatch {
+                expected: 4,
+                actual: 3,
+            },
+        })
+    );
+    assert_eq!(
+        try_parse("XMAS\nSÄMX\n"),
+        Err(GridShapeError {
+            line_num: 2,
+            kind: GridShapeErrorKind::NonAscii { byte_offset: 1 },
+        })
+    );
+    assert_eq!(
+        try_parse("XMÄS\nSAMX\n"),
+        Err(GridShapeError {
+            line_num: 1,
+            kind: GridShapeErrorKind::NonAscii { byte_offset: 2 },
+        })
+    );
+    assert_eq!(
+        try_parse("XMAS\nSAMX\n").map(|grid| grid.size().into_row_major()),
+        Ok((2, 4))
+    );
+}
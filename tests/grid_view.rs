@@ -0,0 +1,115 @@
+use advent_of_code_2024::{
+    grid::{view::GridView, Grid},
+    space::d2::{Coords, Size},
+};
+
+const SOURCE: &str = "\
+abc
+def
+";
+
+fn parse_grid(input: &str) -> Grid<u8> {
+    Grid::from_ascii_lines(input.lines(), |_coords, byte| byte)
+}
+
+fn render(view: &impl GridView<Tile = u8>) -> String {
+    view.to_grid()
+        .rows()
+        .map(|row| format!("{}\n", String::from_utf8(row.to_vec()).unwrap()))
+        .collect()
+}
+
+fn coords(row_major: (usize, usize)) -> Coords {
+    Coords::from_row_major(row_major)
+}
+
+#[test]
+fn rotated() {
+    let grid = parse_grid(SOURCE);
+
+    assert_eq!(render(&grid.rotated(0)), SOURCE);
+    assert_eq!(render(&grid.rotated(1)), "da\neb\nfc\n");
+    assert_eq!(render(&grid.rotated(2)), "fed\ncba\n");
+    assert_eq!(render(&grid.rotated(3)), "cf\nbe\nad\n");
+    assert_eq!(render(&grid.rotated(4)), SOURCE);
+
+    let rotated = grid.rotated(1);
+    assert_eq!(rotated.size(), Size::from_row_major((3, 2)));
+    assert_eq!(rotated.to_source(coords((0, 0))), Some(coords((1, 0))));
+    assert_eq!(rotated.to_source(coords((2, 1))), Some(coords((0, 2))));
+    assert_eq!(rotated.to_source(coords((0, 2))), None);
+    assert_eq!(rotated[coords((1, 0))], b'e');
+}
+
+#[test]
+fn flipped() {
+    let grid = parse_grid(SOURCE);
+
+    let flipped = grid.flipped_horizontally();
+    assert_eq!(render(&flipped), "cba\nfed\n");
+    assert_eq!(flipped.to_source(coords((0, 0))), Some(coords((0, 2))));
+    assert_eq!(flipped.to_source(coords((1, 2))), Some(coords((1, 0))));
+
+    let flipped = grid.flipped_vertically();
+    assert_eq!(render(&flipped), "def\nabc\n");
+    assert_eq!(flipped.to_source(coords((0, 0))), Some(coords((1, 0))));
+    assert_eq!(flipped.to_source(coords((1, 2))), Some(coords((0, 2))));
+
+    assert_eq!(
+        render(&grid.flipped_horizontally().to_grid().flipped_horizontally()),
+        SOURCE
+    );
+}
+
+#[test]
+fn transposed() {
+    let grid = parse_grid(SOURCE);
+
+    let transposed = grid.transposed();
+    assert_eq!(transposed.size(), Size::from_row_major((3, 2)));
+    assert_eq!(render(&transposed), "ad\nbe\ncf\n");
+    for (view_coords, _tile) in transposed.iter() {
+        let (row_idx, col_idx) = view_coords.into_row_major();
+        assert_eq!(
+            transposed.to_source(view_coords),
+            Some(coords((col_idx, row_idx)))
+        );
+    }
+    assert_eq!(transposed.to_source(coords((3, 0))), None);
+
+    assert_eq!(render(&transposed.to_grid().transposed()), SOURCE);
+}
+
+#[test]
+fn window() {
+    let grid = parse_grid(SOURCE);
+
+    let window = grid
+        .window(coords((0, 1)), Size::from_row_major((2, 2)))
+        .unwrap();
+    assert_eq!(render(&window), "bc\nef\n");
+    assert_eq!(window.to_source(coords((1, 0))), Some(coords((1, 1))));
+    assert_eq!(window.to_source(coords((0, 2))), None);
+    assert_eq!(window.get(coords((2, 0))), None);
+
+    let window = grid
+        .window(coords((1, 0)), Size::from_row_major((1, 3)))
+        .unwrap();
+    assert_eq!(render(&window), "def\n");
+
+    // Doesn't fit.
+    assert!(grid
+        .window(coords((1, 2)), Size::from_row_major((1, 2)))
+        .is_none());
+    assert!(grid
+        .window(coords((0, 0)), Size::from_row_major((3, 1)))
+        .is_none());
+
+    // Views compose.
+    let rotated = grid.rotated(1);
+    let window = rotated
+        .window(coords((1, 0)), Size::from_row_major((2, 2)))
+        .unwrap();
+    assert_eq!(render(&window), "eb\nfc\n");
+    assert_eq!(window[coords((1, 1))], b'c');
+}
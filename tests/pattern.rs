@@ -0,0 +1,121 @@
+use advent_of_code_2024::{
+    grid::Grid,
+    pattern::{find_matches, Pattern, PatternMatch, Symmetry},
+    space::d2::Coords,
+};
+
+fn parse_grid(input: &str) -> Grid<u8> {
+    Grid::from_ascii_lines(input.lines(), |_coords, byte| byte)
+}
+
+#[test]
+fn orientations() {
+    let num_orientations =
+        |template, symmetry| Pattern::parse(template).orientations(symmetry).len();
+
+    // Chiral, so every orientation is distinct.
+    let l_shape = "AB\nC.\n";
+    assert_eq!(num_orientations(l_shape, Symmetry::None), 1);
+    assert_eq!(num_orientations(l_shape, Symmetry::Rotations), 4);
+    assert_eq!(
+        num_orientations(l_shape, Symmetry::RotationsAndReflections),
+        8
+    );
+
+    // Each reflection is also a rotation.
+    let x_mas = "M.S\n.A.\nM.S\n";
+    assert_eq!(num_orientations(x_mas, Symmetry::Rotations), 4);
+    assert_eq!(
+        num_orientations(x_mas, Symmetry::RotationsAndReflections),
+        4
+    );
+    let word = "AB\n";
+    assert_eq!(num_orientations(word, Symmetry::Rotations), 4);
+    assert_eq!(num_orientations(word, Symmetry::RotationsAndReflections), 4);
+
+    // Fully symmetric
+    let cross = "A.A\n.B.\nA.A\n";
+    assert_eq!(num_orientations(cross, Symmetry::Rotations), 1);
+    assert_eq!(
+        num_orientations(cross, Symmetry::RotationsAndReflections),
+        1
+    );
+    let palindrome = "ABA\n";
+    assert_eq!(num_orientations(palindrome, Symmetry::Rotations), 2);
+    assert_eq!(
+        num_orientations(palindrome, Symmetry::RotationsAndReflections),
+        2
+    );
+
+    let pattern = Pattern::parse(l_shape);
+    for (orientation, oriented) in pattern.orientations(Symmetry::RotationsAndReflections) {
+        assert_eq!(oriented, pattern.oriented(orientation));
+    }
+}
+
+#[test]
+fn reflected_matches() {
+    let pattern = Pattern::parse("AB\nC.\n");
+    let mirror_image = parse_grid("BA\nXC\n");
+
+    assert_eq!(
+        find_matches(&mirror_image, &pattern, Symmetry::Rotations).count(),
+        0
+    );
+    let matches = find_matches(&mirror_image, &pattern, Symmetry::RotationsAndReflections)
+        .collect::<Vec<_>>();
+    assert_eq!(matches.len(), 1);
+    let PatternMatch {
+        origin,
+        orientation,
+    } = matches[0];
+    assert_eq!(origin, Coords::from_row_major((0, 0)));
+    assert!(orientation.mirrored);
+
+    // Symmetric patterns are only counted once per placement.
+    let cross = Pattern::parse("A.A\n.B.\nA.A\n");
+    let grid = parse_grid("AXA\nXBX\nAXA\n");
+    assert_eq!(
+        find_matches(&grid, &cross, Symmetry::RotationsAndReflections).count(),
+        1
+    );
+}
+
+#[test]
+fn pattern_larger_than_grid() {
+    let grid = parse_grid("AB\nCD\n");
+    let symmetry = Symmetry::RotationsAndReflections;
+
+    let too_tall = Pattern::parse("A\nC\nE\n");
+    assert_eq!(find_matches(&grid, &too_tall, symmetry).count(), 0);
+    let too_big = Pattern::parse("...\n...\n...\n");
+    assert_eq!(find_matches(&grid, &too_big, symmetry).count(), 0);
+    assert_eq!(
+        find_matches(&parse_grid(""), &Pattern::parse("A\n"), symmetry).count(),
+        0
+    );
+
+    // Too wide as written, but fits once rotated.
+    let column = parse_grid("A\nB\nC\n");
+    let row_pattern = Pattern::parse("ABC\n");
+    assert_eq!(
+        find_matches(&column, &row_pattern, Symmetry::None).count(),
+        0
+    );
+    assert_eq!(
+        find_matches(&column, &row_pattern, Symmetry::Rotations).count(),
+        1
+    );
+}
+
+#[test]
+#[should_panic(expected = "can't search for an empty pattern")]
+fn empty_pattern() {
+    Pattern::parse("");
+}
+
+#[test]
+#[should_panic(expected = "can't search for an empty pattern")]
+fn empty_pattern_rows() {
+    Pattern::parse("\n\n");
+}
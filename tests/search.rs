@@ -0,0 +1,69 @@
+use advent_of_code_2024::search::{astar, bfs, dijkstra, Predecessors};
+
+#[test]
+fn zero_cost_edges_back_to_starts() {
+    // a <-> b at no cost, then b -> c
+    let successors = |state: &char| match state {
+        'a' => vec![('b', 0)],
+        'b' => vec![('a', 0), ('c', 1)],
+        _ => vec![],
+    };
+
+    let result = dijkstra(['a'], successors, |&state| state == 'c', Predecessors::All);
+    assert_eq!(result.distance(&'c'), Some(1));
+    assert_eq!(result.predecessors(&'a'), &[]);
+    assert_eq!(result.predecessors(&'b'), &['a']);
+    assert_eq!(result.path(), Some(vec!['a', 'b', 'c']));
+
+    // Two starts joined by a zero-cost edge
+    let successors = |state: &char| match state {
+        'a' => vec![('b', 0), ('c', 2)],
+        'b' => vec![('a', 0), ('c', 2)],
+        _ => vec![],
+    };
+    let result = astar(
+        ['a', 'b'],
+        successors,
+        |_state| 0,
+        |&state| state == 'c',
+        Predecessors::All,
+    );
+    assert_eq!(result.distance(&'c'), Some(2));
+    assert_eq!(result.predecessors(&'a'), &[]);
+    assert_eq!(result.predecessors(&'b'), &[]);
+    assert_eq!(result.predecessors(&'c'), &['a', 'b']);
+    assert_eq!(
+        result.states_on_shortest_paths(),
+        ['a', 'b', 'c'].into_iter().collect()
+    );
+}
+
+#[test]
+fn zero_cost_cycles() {
+    // a -> b -> c -> b, all at no cost, then c -> d
+    let successors = |state: &char| match state {
+        'a' => vec![('b', 0)],
+        'b' => vec![('c', 0)],
+        'c' => vec![('b', 0), ('d', 3)],
+        _ => vec![],
+    };
+    let result = dijkstra(['a'], successors, |&state| state == 'd', Predecessors::All);
+    assert_eq!(result.distance(&'d'), Some(3));
+    assert_eq!(result.path(), Some(vec!['a', 'b', 'c', 'd']));
+}
+
+#[test]
+fn bfs_edges_back_to_starts() {
+    let successors = |state: &u8| match state {
+        0 => vec![1],
+        1 => vec![0, 2],
+        2 => vec![1, 3],
+        _ => vec![],
+    };
+    let result = bfs([0, 1], successors, |&state| state == 3, Predecessors::All);
+    assert_eq!(result.distance(&3), Some(2));
+    assert_eq!(result.predecessors(&0), &[]);
+    assert_eq!(result.predecessors(&1), &[]);
+    assert_eq!(result.predecessors(&2), &[1]);
+    assert_eq!(result.path(), Some(vec![1, 2, 3]));
+}
@@ -0,0 +1,60 @@
+use advent_of_code_2024::simulate::{
+    find_cycle_brent, find_cycle_hashed, state_after, Cycle, Outcome,
+};
+
+/// `0, 1, 2, 3, 4, 5, 6, 3, 4, 5, 6, 3, …`; i.e., a tail of 3 states leading into a cycle of 4.
+fn rho_step(&state: &u32) -> Option<u32> {
+    Some(if state == 6 { 3 } else { state + 1 })
+}
+
+/// Reference implementation of the state after `num_steps` steps of [`rho_step`].
+fn rho_state_after(num_steps: usize) -> u32 {
+    let state = if num_steps < 3 {
+        num_steps
+    } else {
+        3 + (num_steps - 3) % 4
+    };
+    state.try_into().unwrap()
+}
+
+#[test]
+fn rho() {
+    let expected = Outcome::Cycled(Cycle { start: 3, len: 4 });
+    assert_eq!(find_cycle_hashed(0, rho_step), expected);
+    assert_eq!(find_cycle_brent(0, rho_step), expected);
+
+    // Starting inside the cycle means there's no tail.
+    let expected = Outcome::Cycled(Cycle { start: 0, len: 4 });
+    assert_eq!(find_cycle_hashed(5, rho_step), expected);
+    assert_eq!(find_cycle_brent(5, rho_step), expected);
+
+    // A fixed point is a cycle of length 1.
+    let expected = Outcome::Cycled(Cycle { start: 2, len: 1 });
+    let step = |&state: &u32| Some((state + 1).min(2));
+    assert_eq!(find_cycle_hashed(0, step), expected);
+    assert_eq!(find_cycle_brent(0, step), expected);
+
+    for num_steps in (0..30).chain([1_000_000_000_000, 1_000_000_000_001, usize::MAX]) {
+        assert_eq!(
+            state_after(0, rho_step, num_steps),
+            Some(rho_state_after(num_steps)),
+            "num_steps: {num_steps}"
+        );
+    }
+}
+
+#[test]
+fn halting() {
+    let step = |&state: &u32| (state < 5).then_some(state + 1);
+    let expected = Outcome::Halted { steps: 5, state: 5 };
+    assert_eq!(find_cycle_hashed(0, step), expected);
+    assert_eq!(find_cycle_brent(0, step), expected);
+
+    let expected = Outcome::Halted { steps: 0, state: 7 };
+    assert_eq!(find_cycle_hashed(7, step), expected);
+    assert_eq!(find_cycle_brent(7, step), expected);
+
+    assert_eq!(state_after(0, step, 3), Some(3));
+    assert_eq!(state_after(0, step, 5), Some(5));
+    assert_eq!(state_after(0, step, 6), None);
+}
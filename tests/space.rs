@@ -0,0 +1,501 @@
+use std::collections::HashSet;
+
+use advent_of_code_2024::{
+    grid::Grid,
+    space::{
+        axis, d2, d3, nd,
+        signed::{self, BoundingBox, SparseGrid},
+        Coord, Offset, PosNeg, RelativeOffset,
+    },
+};
+
+#[test]
+fn d3_rel_offsets() {
+    let offset = |x: i64, y: i64, z: i64| d3::RelativeOffset {
+        x: x.into(),
+        y: y.into(),
+        z: z.into(),
+    };
+
+    let bounds = d3::Size::from_xyz((2, 3, 4));
+    let origin = d3::Coords::from_xyz((1, 0, 3));
+    let target = d3::Coords::from_xyz((0, 2, 1));
+
+    // NOTE: `a - b` is the offset that takes you from `a` to `b`.
+    assert_eq!(origin - target, offset(-1, 2, -2));
+    assert_eq!(target - origin, -offset(-1, 2, -2));
+    assert_eq!(origin - origin, offset(0, 0, 0));
+
+    assert_eq!(
+        d3::apply_rel_offset(bounds, origin, origin - target),
+        Some(target)
+    );
+    assert_eq!(
+        d3::apply_rel_offset(bounds, target, target - origin),
+        Some(origin)
+    );
+
+    // Past the upper bound of each axis.
+    assert_eq!(d3::apply_rel_offset(bounds, origin, offset(1, 0, 0)), None);
+    assert_eq!(d3::apply_rel_offset(bounds, origin, offset(0, 3, 0)), None);
+    assert_eq!(d3::apply_rel_offset(bounds, origin, offset(0, 0, 1)), None);
+
+    // Below zero on each axis.
+    assert_eq!(d3::apply_rel_offset(bounds, origin, offset(-2, 0, 0)), None);
+    assert_eq!(d3::apply_rel_offset(bounds, origin, offset(0, -1, 0)), None);
+    assert_eq!(d3::apply_rel_offset(bounds, origin, offset(0, 0, -4)), None);
+}
+
+#[test]
+fn nd_neighborhoods() {
+    fn check<const N: usize>() {
+        let moore = nd::moore_offsets::<N>().collect::<Vec<_>>();
+        assert_eq!(moore.len(), 3usize.pow(N.try_into().unwrap()) - 1);
+        assert_eq!(
+            moore.iter().copied().collect::<HashSet<_>>().len(),
+            moore.len()
+        );
+
+        let von_neumann = nd::von_neumann_offsets::<N>().collect::<Vec<_>>();
+        assert_eq!(von_neumann.len(), 2 * N);
+        assert_eq!(
+            von_neumann.iter().copied().collect::<HashSet<_>>().len(),
+            von_neumann.len()
+        );
+        assert!(von_neumann.iter().all(|offset| moore.contains(offset)));
+    }
+    check::<1>();
+    check::<2>();
+    check::<3>();
+    check::<4>();
+
+    // The neighborhoods should agree with the 2D stencils.
+    assert_eq!(
+        nd::moore_offsets::<2>()
+            .map(d2::RelativeOffset::from)
+            .collect::<HashSet<_>>(),
+        d2::MOORE_STENCIL.into_iter().collect()
+    );
+    assert_eq!(
+        nd::von_neumann_offsets::<2>()
+            .map(d2::RelativeOffset::from)
+            .collect::<HashSet<_>>(),
+        d2::VON_NEUMANN_STENCIL.into_iter().collect()
+    );
+
+    // Neighbors are clipped at the bounds.
+    let bounds = nd::Size::from_array([3, 3, 3]);
+    let count_neighbors = |coords| {
+        let coords = nd::Coords::from_array(coords);
+        (
+            nd::moore_neighbors(bounds, coords).count(),
+            nd::von_neumann_neighbors(bounds, coords).count(),
+        )
+    };
+    assert_eq!(count_neighbors([1, 1, 1]), (26, 6));
+    assert_eq!(count_neighbors([0, 0, 0]), (7, 3));
+    assert_eq!(count_neighbors([0, 1, 2]), (11, 4));
+}
+
+#[test]
+fn nd_d2_round_trip() {
+    let size = d2::Size::from_row_major((4, 7));
+    assert_eq!(nd::Size::from(size).into_array(), [4, 7]);
+    assert_eq!(d2::Size::from(nd::Size::from(size)), size);
+
+    let coords = d2::Coords::from_row_major((3, 5));
+    assert_eq!(nd::Coords::from(coords).into_array(), [3, 5]);
+    assert_eq!(d2::Coords::from(nd::Coords::from(coords)), coords);
+
+    let offset = d2::RelativeOffset::from((-2, 6));
+    assert_eq!(
+        d2::RelativeOffset::from(nd::RelativeOffset::from(offset)),
+        offset
+    );
+
+    // Applying an offset gives the same result in either representation.
+    let apply_nd = |offset: d2::RelativeOffset| {
+        nd::apply_rel_offset(size.into(), coords.into(), offset.into()).map(d2::Coords::from)
+    };
+    for offset in [offset, d2::RelativeOffset::from((-2, 1))] {
+        assert_eq!(apply_nd(offset), d2::apply_rel_offset(size, coords, offset));
+    }
+    assert_eq!(apply_nd(offset), None);
+    assert_eq!(
+        apply_nd(d2::RelativeOffset::from((-2, 1))),
+        Some(d2::Coords::from_row_major((1, 6)))
+    );
+}
+
+#[test]
+fn signed_bounding_box() {
+    let mut grid = SparseGrid::new();
+    assert_eq!(grid.bounding_box(), None);
+
+    for (coords, c) in [((-2, 3), 'a'), ((1, -4), 'b'), ((0, 0), 'c')] {
+        grid.insert(signed::Coords::from_row_major(coords), c);
+    }
+    let bounding_box = grid.bounding_box().unwrap();
+    assert_eq!(
+        bounding_box,
+        BoundingBox {
+            min: signed::Coords::from_row_major((-2, -4)),
+            max: signed::Coords::from_row_major((1, 3)),
+        }
+    );
+    assert_eq!(bounding_box.size(), d2::Size::from_row_major((4, 8)));
+
+    for (coords, _c) in grid.iter() {
+        let unsigned = bounding_box.to_unsigned(coords).unwrap();
+        assert_eq!(bounding_box.from_unsigned(unsigned), Some(coords));
+    }
+    assert_eq!(
+        bounding_box.to_unsigned(signed::Coords::from_row_major((-2, 3))),
+        Some(d2::Coords::from_row_major((0, 7)))
+    );
+    assert_eq!(
+        bounding_box.to_unsigned(signed::Coords::from_row_major((2, 0))),
+        None
+    );
+    assert_eq!(
+        bounding_box.from_unsigned(d2::Coords::from_row_major((3, 7))),
+        Some(signed::Coords::from_row_major((1, 3)))
+    );
+    assert_eq!(
+        bounding_box.from_unsigned(d2::Coords::from_row_major((4, 0))),
+        None
+    );
+}
+
+#[test]
+#[should_panic(expected = "spans more coordinates than fit in a `u64`")]
+fn signed_bounding_box_size_overflow() {
+    BoundingBox {
+        min: signed::Coords::from_row_major((i64::MIN, 0)),
+        max: signed::Coords::from_row_major((i64::MAX, 0)),
+    }
+    .size();
+}
+
+#[test]
+fn wrapping_rel_offsets() {
+    let modulus = Coord::<axis::Any>::new(5);
+    let wrap = |coord: usize, offset: i64| {
+        Coord::<axis::Any>::new(coord)
+            .wrapping_rel_offset(offset.into(), modulus)
+            .into_inner()
+    };
+    assert_eq!(wrap(3, 1), 4);
+    assert_eq!(wrap(3, 2), 0);
+    assert_eq!(wrap(3, -3), 0);
+    assert_eq!(wrap(3, -4), 4);
+    assert_eq!(wrap(0, -1), 4);
+    assert_eq!(wrap(4, 1), 0);
+    assert_eq!(wrap(3, 5), 3);
+    assert_eq!(wrap(3, -5), 3);
+    assert_eq!(wrap(3, 17), 0);
+    assert_eq!(wrap(3, -17), 1);
+    assert_eq!(wrap(3, i64::MAX), wrap(3, i64::MAX % 5));
+    assert_eq!(wrap(3, -i64::MAX), wrap(3, -(i64::MAX % 5)));
+
+    let bounds = d2::Size::from_row_major((7, 11));
+    let origin = d2::Coords::from_row_major((2, 9));
+    let step = d2::RelativeOffset::from((-3, 4));
+    let mut expected = origin;
+    for times in 0..100 {
+        assert_eq!(
+            d2::apply_rel_offset_wrapping_n(bounds, origin, step, times),
+            expected
+        );
+        expected = d2::apply_rel_offset_wrapping(bounds, expected, step);
+    }
+
+    // NOTE: `step * times` overflows here, but the period of each axis divides `7 * 11`.
+    let times = usize::MAX;
+    let period = 7 * 11;
+    assert_eq!(
+        d2::apply_rel_offset_wrapping_n(bounds, origin, step, times),
+        d2::apply_rel_offset_wrapping_n(bounds, origin, step, times % period)
+    );
+    let step = d2::RelativeOffset::from((-i64::MAX, i64::MAX));
+    assert_eq!(
+        d2::apply_rel_offset_wrapping_n(bounds, origin, step, times),
+        d2::apply_rel_offset_wrapping_n(
+            bounds,
+            origin,
+            d2::RelativeOffset::from((-(i64::MAX % 7), i64::MAX % 11)),
+            times % period
+        )
+    );
+}
+
+#[test]
+#[should_panic(expected = "is out of bounds of")]
+fn wrapping_rel_offsets_in_empty_bounds() {
+    d2::apply_rel_offset_wrapping_n(
+        d2::Size::from_row_major((0, 3)),
+        d2::Coords::from_row_major((0, 0)),
+        d2::RelativeOffset::from((1, 1)),
+        2,
+    );
+}
+
+#[test]
+fn rel_offset_arithmetic() {
+    let offset = RelativeOffset::from;
+
+    assert_eq!(offset(3).checked_add(offset(4)), Some(offset(7)));
+    assert_eq!(offset(-3).checked_add(offset(-4)), Some(offset(-7)));
+    assert_eq!(offset(3).checked_add(offset(-4)), Some(offset(-1)));
+    assert_eq!(offset(-3).checked_add(offset(4)), Some(offset(1)));
+    assert_eq!(offset(4).checked_add(offset(-4)), Some(offset(0)));
+    assert_eq!(offset(3).checked_sub(offset(4)), Some(offset(-1)));
+    assert_eq!(offset(-3).checked_sub(offset(-4)), Some(offset(1)));
+
+    let max = RelativeOffset {
+        value: Offset::new(usize::MAX),
+        sign: PosNeg::Positive,
+    };
+    assert_eq!(max.checked_add(offset(1)), None);
+    assert_eq!((-max).checked_add(offset(-1)), None);
+    assert_eq!((-max).checked_sub(offset(1)), None);
+    assert_eq!(
+        max.checked_add(offset(-1)),
+        Some(RelativeOffset {
+            value: Offset::new(usize::MAX - 1),
+            sign: PosNeg::Positive,
+        })
+    );
+    assert_eq!(max.checked_add(-max), Some(offset(0)));
+
+    assert_eq!(offset(-12).checked_div_exact(4), Some(offset(-3)));
+    assert_eq!(offset(-12).checked_div_exact(5), None);
+    assert_eq!(offset(12).checked_div_exact(0), None);
+    assert_eq!(offset(0).checked_div_exact(7), Some(offset(0)));
+}
+
+#[test]
+fn rel_offset_zero_sign() {
+    let positive_zero = RelativeOffset {
+        value: Offset::new(0),
+        sign: PosNeg::Positive,
+    };
+    let negative_zero = RelativeOffset {
+        value: Offset::new(0),
+        sign: PosNeg::Negative,
+    };
+    assert_eq!(positive_zero, negative_zero);
+    assert_eq!(-positive_zero, positive_zero);
+    assert_eq!(
+        HashSet::from([positive_zero, negative_zero]).len(),
+        1,
+        "equal offsets must hash the same"
+    );
+    assert_ne!(RelativeOffset::from(1), RelativeOffset::from(-1));
+    assert_eq!(i64::try_from(negative_zero), Ok(0));
+
+    // e.g., from subtracting equal coordinates
+    let coords = d2::Coords::from_row_major((2, 3));
+    assert_eq!(coords - coords, d2::RelativeOffset::from((0, 0)));
+}
+
+#[test]
+fn rel_offset_i64_conversions() {
+    for value in [0, 1, -1, 42, -42, i64::MAX, i64::MIN] {
+        assert_eq!(i64::try_from(RelativeOffset::from(value)), Ok(value));
+    }
+
+    let too_big = RelativeOffset {
+        value: Offset::new(usize::try_from(i64::MAX).unwrap() + 1),
+        sign: PosNeg::Positive,
+    };
+    assert!(i64::try_from(too_big).is_err());
+    assert!(i64::try_from(-too_big).is_ok(), "`i64::MIN` should fit");
+    assert!(i64::try_from(RelativeOffset {
+        value: Offset::new(usize::MAX),
+        sign: PosNeg::Negative,
+    })
+    .is_err());
+
+    let offset = d2::RelativeOffset::from((-4, 6));
+    assert_eq!(<(i64, i64)>::try_from(offset), Ok((-4, 6)));
+    let offset = d2::RelativeOffset {
+        row: RelativeOffset::from(1),
+        col: too_big,
+    };
+    assert!(<(i64, i64)>::try_from(offset).is_err());
+}
+
+#[test]
+fn rel_offset_reduced() {
+    assert_eq!(
+        d2::RelativeOffset::from((-4, 6)).reduced(),
+        d2::RelativeOffset::from((-2, 3))
+    );
+    assert_eq!(
+        d2::RelativeOffset::from((0, -5)).reduced(),
+        d2::RelativeOffset::from((0, -1))
+    );
+    assert_eq!(
+        d2::RelativeOffset::from((7, 5)).reduced(),
+        d2::RelativeOffset::from((7, 5))
+    );
+    assert_eq!(
+        d2::RelativeOffset::from((0, 0)).reduced(),
+        d2::RelativeOffset::from((0, 0))
+    );
+    assert!(d2::RelativeOffset::from((0, 0)).reduced().is_zero());
+}
+
+#[test]
+fn rel_offsets_out_of_bounds_before_overflow() {
+    let row_below_zero_col_overflows = d2::RelativeOffset {
+        row: (-1).into(),
+        col: RelativeOffset {
+            value: Offset::new(usize::MAX),
+            sign: PosNeg::Positive,
+        },
+    };
+    let origin = d2::Coords::from_row_major((0, 1));
+    let bounds = d2::Size::from_row_major((3, 3));
+    assert_eq!(
+        d2::apply_rel_offset(bounds, origin, row_below_zero_col_overflows),
+        None
+    );
+    assert_eq!(
+        d2::checked_apply_rel_offset(bounds, origin, row_below_zero_col_overflows),
+        None
+    );
+
+    let huge = RelativeOffset {
+        value: Offset::new(usize::MAX),
+        sign: PosNeg::Positive,
+    };
+    assert_eq!(
+        d3::apply_rel_offset(
+            d3::Size::from_xyz((3, 3, 3)),
+            d3::Coords::from_xyz((1, 0, 1)),
+            d3::RelativeOffset {
+                x: 0.into(),
+                y: (-1).into(),
+                z: huge,
+            },
+        ),
+        None
+    );
+    assert_eq!(
+        nd::apply_rel_offset(
+            nd::Size::from_array([3, 3]),
+            nd::Coords::from_array([0, 1]),
+            nd::RelativeOffset {
+                axes: [(-1).into(), huge],
+            },
+        ),
+        None
+    );
+}
+
+#[test]
+#[should_panic(expected = "overflowed")]
+fn rel_offset_overflow_in_bounds_row() {
+    d2::apply_rel_offset(
+        d2::Size::from_row_major((3, 3)),
+        d2::Coords::from_row_major((1, 1)),
+        d2::RelativeOffset {
+            row: 1.into(),
+            col: RelativeOffset {
+                value: Offset::new(usize::MAX),
+                sign: PosNeg::Positive,
+            },
+        },
+    );
+}
+
+#[test]
+fn d2_neighbors_clipping() {
+    let bounds = d2::Size::from_row_major((3, 4));
+    let neighbors4 = |origin| {
+        d2::neighbors4(bounds, d2::Coords::from_row_major(origin))
+            .map(|(_offset, coords)| coords.into_row_major())
+            .collect::<Vec<_>>()
+    };
+    let neighbors8 = |origin| {
+        d2::neighbors8(bounds, d2::Coords::from_row_major(origin))
+            .map(|(_offset, coords)| coords.into_row_major())
+            .collect::<Vec<_>>()
+    };
+
+    // Top-left corner
+    assert_eq!(neighbors4((0, 0)), [(0, 1), (1, 0)]);
+    assert_eq!(neighbors8((0, 0)), [(0, 1), (1, 1), (1, 0)]);
+    // Top edge
+    assert_eq!(neighbors4((0, 2)), [(0, 3), (1, 2), (0, 1)]);
+    assert_eq!(neighbors8((0, 2)), [(0, 3), (1, 3), (1, 2), (1, 1), (0, 1)]);
+    // Right edge
+    assert_eq!(neighbors4((1, 3)), [(0, 3), (2, 3), (1, 2)]);
+    assert_eq!(neighbors8((1, 3)), [(0, 3), (2, 3), (2, 2), (1, 2), (0, 2)]);
+    // Bottom-right corner
+    assert_eq!(neighbors4((2, 3)), [(1, 3), (2, 2)]);
+    assert_eq!(neighbors8((2, 3)), [(1, 3), (2, 2), (1, 2)]);
+    // Bottom-left corner
+    assert_eq!(neighbors4((2, 0)), [(1, 0), (2, 1)]);
+    assert_eq!(neighbors8((2, 0)), [(1, 0), (1, 1), (2, 1)]);
+    // Interior
+    assert_eq!(neighbors4((1, 1)), [(0, 1), (1, 2), (2, 1), (1, 0)]);
+    assert_eq!(
+        neighbors8((1, 1)),
+        [
+            (0, 1),
+            (0, 2),
+            (1, 2),
+            (2, 2),
+            (2, 1),
+            (2, 0),
+            (1, 0),
+            (0, 0)
+        ]
+    );
+
+    // Offsets are reported alongside the coordinates they lead to.
+    let origin = d2::Coords::from_row_major((0, 0));
+    assert!(d2::neighbors8(bounds, origin)
+        .all(|(offset, coords)| d2::apply_rel_offset(bounds, origin, offset) == Some(coords)));
+
+    // A single cell has no neighbours.
+    let bounds = d2::Size::from_row_major((1, 1));
+    assert_eq!(d2::neighbors4(bounds, origin).count(), 0);
+    assert_eq!(d2::neighbors8(bounds, origin).count(), 0);
+
+    // Custom stencils clip the same way.
+    let stencil = [(0, 2), (2, 0), (-2, 0), (1, -1)].map(d2::RelativeOffset::from);
+    let bounds = d2::Size::from_row_major((3, 3));
+    assert_eq!(
+        d2::neighbors_with(bounds, d2::Coords::from_row_major((0, 1)), &stencil)
+            .map(|(_offset, coords)| coords.into_row_major())
+            .collect::<Vec<_>>(),
+        [(2, 1), (1, 0)]
+    );
+}
+
+#[test]
+fn d2_xy_round_trip() {
+    for (row, col) in [(0, 0), (0, 5), (3, 0), (3, 5), (usize::MAX, 1)] {
+        let coords = d2::Coords::from_row_major((row, col));
+        assert_eq!(coords.row.into_inner(), row);
+        assert_eq!(coords.col.into_inner(), col);
+        assert_eq!(coords.into_row_major(), (row, col));
+        assert_eq!(coords.into_xy(), (col, row));
+        assert_eq!(d2::Coords::from_xy(coords.into_xy()), coords);
+
+        let size = d2::Size::from_row_major((row, col));
+        assert_eq!(size.into_row_major(), (row, col));
+        assert_eq!(size.into_xy(), (col, row));
+        assert_eq!(d2::Size::from_xy(size.into_xy()), size);
+    }
+
+    // `y` is the line and `x` the byte within it.
+    let grid = Grid::from_ascii_lines("abc\ndef\n".lines(), |_coords, byte| byte);
+    assert_eq!(grid.size().into_xy(), (3, 2));
+    assert_eq!(grid[d2::Coords::from_xy((2, 0))], b'c');
+    assert_eq!(grid[d2::Coords::from_xy((0, 1))], b'd');
+}